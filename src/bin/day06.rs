@@ -24,6 +24,8 @@ enum ParseError {
     InvalidFormat(&'static str),
     #[error("Failed to parse number")]
     ParseFailed(#[from] ParseIntError),
+    #[error("Concatenated {0} does not fit in 128 bits")]
+    Overflow(&'static str),
 }
 
 /// Input today is comically simple.
@@ -77,6 +79,68 @@ fn parse_input(input: &str) -> Result<Vec<Race>, ParseError> {
     }
 }
 
+/// Count the number of ways to beat `record` in a race lasting `time`.
+///
+/// Holding the button for `charge` milliseconds covers a distance
+/// of `charge * (time - charge)`, which is symmetric around `time / 2`.
+/// That means the winning charges form a single unbroken range, so
+/// rather than trying every possible charge, we binary search for the
+/// smallest one that wins and mirror it to find the largest.
+fn ways_to_win(time: u128, record: u128) -> u128 {
+    // If the multiplication overflows, the distance
+    // is definitely bigger than any record we can hold.
+    let wins = |charge: u128| {
+        charge
+            .checked_mul(time - charge)
+            .is_none_or(|distance| distance > record)
+    };
+
+    let half = time / 2;
+    // The best charge is right in the middle,
+    // so if that can't win, nothing can.
+    if !wins(half) {
+        return 0;
+    }
+
+    // Find the first charge that wins. Everything
+    // below `low` loses and `high` always wins.
+    let mut low = 0;
+    let mut high = half;
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if wins(mid) {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+
+    // Every charge from `low` to `time - low` wins.
+    time - 2 * low + 1
+}
+
+/// Glue the digits of several numbers together into one big number.
+///
+/// This is done with checked arithmetic so that a race
+/// built from too many columns gives an error instead of
+/// silently wrapping around.
+fn concatenate(
+    mut numbers: impl Iterator<Item = u32>,
+    label: &'static str,
+) -> Result<u128, ParseError> {
+    numbers.try_fold(0u128, |total, number| {
+        // Shift the total over by however many digits
+        // the next number has, then tack the number on.
+        let shift = 10u128
+            .checked_pow(number.checked_ilog10().unwrap_or(0) + 1)
+            .ok_or(ParseError::Overflow(label))?;
+        total
+            .checked_mul(shift)
+            .and_then(|total| total.checked_add(number.into()))
+            .ok_or(ParseError::Overflow(label))
+    })
+}
+
 /// Part 1
 /// ------
 ///
@@ -86,25 +150,11 @@ fn parse_input(input: &str) -> Result<Vec<Race>, ParseError> {
 /// whatever time we have remaining.
 ///
 /// For each race, how many ways can we win?
-fn part_one(data: &[Race]) -> usize {
+fn part_one(data: &[Race]) -> u128 {
     data.iter()
-        // For each race...
-        .map(|race| {
-            let total_time = race.0;
-            let record = race.1;
-            // And for every possible amount of charge time
-            // besides the two that are guaranteed to be
-            // zero (no charge time and all charge time)...
-            (1..total_time - 1)
-                // Get the total amount of distance traveled...
-                .map(|charge| charge * (total_time - charge))
-                // ...and see if it beats the distance record
-                .filter(|&distance| distance > record)
-                // Return the number of times we do in fact
-                // win the race.
-                .count()
-        })
-        // Multiply the scores of all of the races
+        // For each race, count the number of ways to win...
+        .map(|race| ways_to_win(race.0.into(), race.1.into()))
+        // ...and multiply the scores of all of the races
         .product()
 }
 
@@ -117,39 +167,16 @@ fn part_one(data: &[Race]) -> usize {
 /// race, and naturally that race has frelling huge
 /// numbers to deal with. Still the question is the
 /// same: how many ways can we win?
-fn part_two(data: &[Race]) -> Result<usize, ParseIntError> {
-    let total_time = data
-        .iter()
-        // Convert each number BACK
-        // into a string
-        .map(|race| race.0.to_string())
-        // Convert the iterator into a Vec
-        // so we have access to a useful method
-        .collect::<Vec<_>>()
-        // Concatenate all of the strings
-        .concat()
-        // Convert the string into a number.
-        //
-        // This is a u64 because it turns out
-        // that this number is frelling huge. Eep.
-        .parse::<u64>()?;
-    // Run the same algorithm for the distance record.
-    let record = data
-        .iter()
-        .map(|race| race.1.to_string())
-        .collect::<Vec<_>>()
-        .concat()
-        .parse::<u64>()?;
-
-    // This is the same algorithm we saw in part one.
+fn part_two(data: &[Race]) -> Result<u128, ParseError> {
+    // Concatenate all of the times into one number.
     //
-    // I could _probably_ extract this into a function,
-    // now that I think of it, but that's a "for later"
-    // optimization that I probably don't need to make.
-    Ok((1..total_time - 1)
-        .map(|charge| charge * (total_time - charge))
-        .filter(|&distance| distance > record)
-        .count())
+    // This is a u128 because it turns out
+    // that this number is frelling huge. Eep.
+    let total_time = concatenate(data.iter().map(|race| race.0), "time")?;
+    // Run the same algorithm for the distance record.
+    let record = concatenate(data.iter().map(|race| race.1), "distance")?;
+
+    Ok(ways_to_win(total_time, record))
 }
 
 fn main() {
@@ -190,4 +217,26 @@ mod test {
 
         assert_eq!(part_two(&data), Ok(71503));
     }
+
+    #[test]
+    fn test_ways_to_win() {
+        assert_eq!(ways_to_win(7, 9), 4);
+        assert_eq!(ways_to_win(30, 200), 9);
+        assert_eq!(ways_to_win(4, 4), 0);
+        assert_eq!(ways_to_win(0, 0), 0);
+        // Large enough that the middle charge overflows.
+        assert_eq!(ways_to_win(u128::MAX, u128::MAX), u128::MAX - 3);
+    }
+
+    #[test]
+    fn test_long_race() {
+        let races = vec![Race(4294967295, 1); 3];
+        assert_eq!(
+            concatenate(races.iter().map(|race| race.0), "time"),
+            Ok(4294967295_4294967295_4294967295)
+        );
+
+        let races = vec![Race(4294967295, 1); 4];
+        assert_eq!(part_two(&races), Err(ParseError::Overflow("time")));
+    }
 }
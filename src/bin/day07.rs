@@ -5,8 +5,13 @@
 //!
 //! We're playing poker today, folks!
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
+use std::fmt::Display;
 use std::fs::read_to_string;
+use std::str::FromStr;
+
+use thiserror::Error;
 
 use advent_2023::ParseError;
 
@@ -14,6 +19,180 @@ use advent_2023::ParseError;
 /// This corresponds to a Jack otherwise.
 const JOKER: u32 = 10;
 
+/// Every card face from weakest to strongest,
+/// using the values produced by `parse_input`.
+const CARDS: [u32; 13] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13];
+
+/// The face printed on each card, indexed by its value minus one.
+const LABELS: [char; 13] = [
+    '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K', 'A',
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// How to break a tie between two hands of the same type.
+enum TieBreak {
+    /// Compare the cards one at a time in the order they
+    /// were dealt. This is what the camels play.
+    FirstCard,
+    /// Compare the largest groups of cards first, then
+    /// the smaller groups, like in a game of poker.
+    Poker,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+/// Something wrong with a variant of Camel Cards.
+enum RulesError {
+    #[error("Card {0} is missing from the ranking")]
+    /// The ranking has to list every card exactly once.
+    MissingCard(u32),
+    #[error("Card {0} is not a real card")]
+    /// A wild card has to be one of the cards in the deck.
+    UnknownWild(u32),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A variant of Camel Cards.
+///
+/// This describes how strong each card is, which cards
+/// are wild, and how to settle hands of the same type.
+/// Rules other than the ones from the puzzle should be
+/// made with `Rules::new`, which checks that they make sense.
+struct Rules {
+    /// The card faces from weakest to strongest.
+    ///
+    /// This must hold every card in `CARDS` exactly once,
+    /// otherwise some cards wouldn't have a strength.
+    order: [u32; 13],
+    /// The cards that can stand in for any other card.
+    wild: Vec<u32>,
+    /// How to order hands of the same type.
+    tie_break: TieBreak,
+}
+
+impl Rules {
+    /// Make a variant of the rules, checking that `order`
+    /// ranks every card and that every wild card is real.
+    fn new(order: [u32; 13], wild: Vec<u32>, tie_break: TieBreak) -> Result<Rules, RulesError> {
+        // With thirteen slots, every card showing up means
+        // none of them can show up twice.
+        if let Some(&card) = CARDS.iter().find(|card| !order.contains(card)) {
+            return Err(RulesError::MissingCard(card));
+        }
+        if let Some(&card) = wild.iter().find(|card| !CARDS.contains(card)) {
+            return Err(RulesError::UnknownWild(card));
+        }
+
+        Ok(Rules {
+            order,
+            wild,
+            tie_break,
+        })
+    }
+
+    /// The rules as described in part one:
+    /// no wild cards and aces are high.
+    fn standard() -> Rules {
+        Rules::new(CARDS, vec![], TieBreak::FirstCard).expect("Standard rules are valid")
+    }
+
+    /// The rules as described in part two: jacks
+    /// become jokers, which are wild but are
+    /// also the weakest card on their own.
+    fn jokers() -> Rules {
+        let mut order = CARDS;
+        // Move the joker to the bottom of the pile.
+        order[..JOKER as usize].rotate_right(1);
+        Rules::new(order, vec![JOKER], TieBreak::FirstCard).expect("Joker rules are valid")
+    }
+
+    /// Check whether a card is wild under these rules.
+    fn is_wild(&self, card: u32) -> bool {
+        self.wild.contains(&card)
+    }
+
+    /// Get how strong a card is on its own,
+    /// with zero being the weakest card.
+    fn strength(&self, card: u32) -> usize {
+        self.order
+            .iter()
+            .position(|&face| face == card)
+            .expect("Every card should be in the rules")
+    }
+
+    /// Get the type of hand under these rules.
+    fn hand_type(&self, hand: &Hand) -> HandType {
+        if self.wild.is_empty() {
+            hand.hand_type()
+        } else {
+            hand.joker_hand_type(self)
+        }
+    }
+
+    /// Figure out which card the wild cards in
+    /// a hand should pretend to be.
+    ///
+    /// The best choice is always to join the biggest
+    /// group of real cards, and since it doesn't matter
+    /// for scoring, ties go to the strongest card.
+    /// Returns `None` if the hand has nothing wild in it.
+    fn joker_target(&self, hand: &Hand) -> Option<u32> {
+        if !hand.cards.iter().any(|&card| self.is_wild(card)) {
+            return None;
+        }
+        let count = |card: &u32| hand.cards.iter().filter(|&other| other == card).count();
+        hand.cards
+            .iter()
+            .filter(|&&card| !self.is_wild(card))
+            .max_by_key(|&card| (count(card), self.strength(*card)))
+            // A hand of nothing but jokers may
+            // as well be the strongest card.
+            .or(self.order.last())
+            .copied()
+    }
+
+    /// Get the card strengths used to settle
+    /// ties between hands of the same type.
+    fn tie_break_key(&self, hand: &Hand) -> Vec<usize> {
+        let mut strengths: Vec<_> = hand.cards.iter().map(|&card| self.strength(card)).collect();
+        if self.tie_break == TieBreak::Poker {
+            let count =
+                |strength: &usize| strengths.iter().filter(|&other| other == strength).count();
+            // Put the biggest groups first, and the
+            // strongest cards first within each size.
+            let mut sorted = strengths.clone();
+            sorted.sort_by_key(|strength| std::cmp::Reverse((count(strength), *strength)));
+            strengths = sorted;
+        }
+        strengths
+    }
+
+    /// Compare two hands under these rules,
+    /// with the weaker hand being the lesser one.
+    fn compare(&self, one: &Hand, two: &Hand) -> Ordering {
+        // `HandType` puts the best hands first,
+        // so the comparison has to be flipped.
+        self.hand_type(two)
+            .cmp(&self.hand_type(one))
+            .then_with(|| self.tie_break_key(one).cmp(&self.tie_break_key(two)))
+    }
+
+    /// Rank every hand and add up the winnings.
+    ///
+    /// The weakest hand has rank 1 and each
+    /// hand wins its bid times its rank.
+    fn total_winnings(&self, data: &[Hand]) -> usize {
+        let mut res = data.to_vec();
+        // Sort the hands, weakest first.
+        res.sort_by(|one, two| self.compare(one, two));
+        res.iter()
+            // Get the rank of each hand...
+            .enumerate()
+            // ...and multiply it by the bid.
+            .map(|(idx, hand)| (idx + 1) * (hand.bid as usize))
+            .sum()
+    }
+}
+
 #[derive(Clone, Debug, Eq)]
 /// A hand of cards, corresponding to a line of input.
 struct Hand {
@@ -62,15 +241,12 @@ impl Hand {
     }
 
     /// Get the type of hand this hand describes,
-    /// assuming that jacks are wild and the
-    /// worst card.
-    ///
-    /// (Granted, this assumes that the Jack
-    /// is represented by a value of `0`, and
-    /// not the usual value of `10`.)
-    fn joker_hand_type(&self) -> HandType {
+    /// assuming that the wild cards given by
+    /// the rules can become any other card.
+    fn joker_hand_type(&self, rules: &Rules) -> HandType {
         // Separate the hand into jokers and non-jokers.
-        let (jokers, cards): (Vec<_>, Vec<u32>) = self.cards.iter().partition(|&card| *card == 0);
+        let (jokers, cards): (Vec<_>, Vec<u32>) =
+            self.cards.iter().partition(|&&card| rules.is_wild(card));
 
         // I'm not sure why `into_iter` works while `iter` doesn't.
         let card_types = cards.into_iter().collect::<HashSet<u32>>();
@@ -184,24 +360,21 @@ impl PartialEq for Hand {
     }
 }
 
-impl PartialOrd for Hand {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for &card in self.cards.iter() {
+            write!(f, "{}", card_label(card))?;
+        }
+        Ok(())
     }
 }
 
-// This seemed a lot smarter when I only had part one to deal with.
-impl Ord for Hand {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.hand_type()
-            .cmp(&other.hand_type())
-            .then(other.cards.cmp(&self.cards))
-    }
+/// Get the face printed on a card.
+fn card_label(card: u32) -> char {
+    LABELS[card as usize - 1]
 }
 
-
-
-#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
 /// The types of hand a card can have.
 ///
 /// Note that this does not store any
@@ -224,6 +397,25 @@ enum HandType {
     HighCard,
 }
 
+impl FromStr for HandType {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        [
+            HandType::FiveOfKind,
+            HandType::FourOfKind,
+            HandType::FullHouse,
+            HandType::ThreeOfKind,
+            HandType::TwoPair,
+            HandType::OnePair,
+            HandType::HighCard,
+        ]
+        .into_iter()
+        .find(|kind| format!("{:?}", kind).eq_ignore_ascii_case(s))
+        .ok_or(ParseError::InvalidFormat("known hand type"))
+    }
+}
+
 /// Input consists of a series of hands.
 ///
 /// A hand consists of five cards and a bid,
@@ -283,20 +475,7 @@ fn parse_input(input: &str) -> Result<Vec<Hand>, ParseError> {
 /// in the group. The answer is the sum of
 /// all of the hands.
 fn part_one(data: &[Hand]) -> usize {
-    let mut res = data.to_vec();
-    // Sort the hands.
-    res.sort();
-    // Make the first hand the weakest.
-    res.reverse();
-    // res.iter().enumerate().for_each(|(idx, hand)| {
-    //     println!("Hand {} is {:?}", idx, hand.cards);
-    // });
-    res.iter()
-        // Get the rank of each hand...
-        .enumerate()
-        // ...and multiply it by the bid.
-        .map(|(idx, hand)| (idx + 1) * (hand.bid as usize))
-        .sum()
+    Rules::standard().total_winnings(data)
 }
 
 /// Part 2
@@ -308,61 +487,198 @@ fn part_one(data: &[Hand]) -> usize {
 /// whatever value makes the most points. The
 /// question is the same: what's our score?
 fn part_two(data: &[Hand]) -> usize {
-    let mut res = data.to_vec();
+    Rules::jokers().total_winnings(data)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// Everything we know about how a single hand was scored.
+struct HandReport {
+    /// Which line of the input this hand came from.
+    line: usize,
+    /// The hand being described.
+    hand: Hand,
+    /// The type of the hand without any jokers.
+    hand_type: HandType,
+    /// The type of the hand when jacks are jokers.
+    joker_type: HandType,
+    /// The card the jokers pretended to be, if any.
+    joker_target: Option<u32>,
+    /// Where the hand placed, with 1 being the weakest.
+    rank: usize,
+    /// How much the hand won, its rank times its bid.
+    winnings: usize,
+}
+
+impl Display for HandReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let target = self.joker_target.map_or('-', card_label);
+        write!(
+            f,
+            "{:>5} {} bid {:>5}  {:<11} jokers {:<11} as {}  rank {:>5}  winnings {:>9}",
+            self.line,
+            self.hand,
+            self.hand.bid,
+            format!("{:?}", self.hand_type),
+            format!("{:?}", self.joker_type),
+            target,
+            self.rank,
+            self.winnings
+        )
+    }
+}
 
-    // Turn the Jokers into zeros.
-    res = res
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// The order to list hands in a report.
+enum SortKey {
+    /// The order they appeared in the input.
+    Line,
+    /// Weakest hand first.
+    Rank,
+    /// Smallest bid first.
+    Bid,
+    /// Smallest winnings first.
+    Winnings,
+}
+
+impl FromStr for SortKey {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "line" => Ok(SortKey::Line),
+            "rank" => Ok(SortKey::Rank),
+            "bid" => Ok(SortKey::Bid),
+            "winnings" => Ok(SortKey::Winnings),
+            _ => Err(ParseError::InvalidFormat("known sort key")),
+        }
+    }
+}
+
+/// Explain how every hand was scored under a set of rules.
+///
+/// The reports come back in input order, and the
+/// winnings add up to the answer for those rules.
+fn report(data: &[Hand], rules: &Rules) -> Vec<HandReport> {
+    let jokers = Rules::jokers();
+    let mut order: Vec<_> = (0..data.len()).collect();
+    order.sort_by(|&one, &two| rules.compare(&data[one], &data[two]));
+
+    let mut res: Vec<_> = data
         .iter()
-        .map(|hand| {
-            let cards = hand
-                .cards
-                .iter()
-                // For every card, check if it's a joker.
-                // If it is, replace it with a dummy zero
-                // to make sure the sorting works the way
-                // it's supposed to.
-                .map(|&card| if card == JOKER { 0 } else { card })
-                .collect::<Vec<_>>();
-
-            // This `expect` is safe since we didn't touch the length
-            // in the previous operation, but we did have to change the type.
-            let cards = <[u32; 5]>::try_from(cards).expect("Operation should be infalliable");
-
-            Hand {
-                bid: hand.bid,
-                cards,
-            }
+        .enumerate()
+        .map(|(line, hand)| HandReport {
+            line: line + 1,
+            hand: hand.clone(),
+            hand_type: hand.hand_type(),
+            joker_type: jokers.hand_type(hand),
+            joker_target: jokers.joker_target(hand),
+            rank: 0,
+            winnings: 0,
         })
         .collect();
+    for (idx, &line) in order.iter().enumerate() {
+        res[line].rank = idx + 1;
+        res[line].winnings = (idx + 1) * (res[line].hand.bid as usize);
+    }
+    res
+}
 
-    // Sort the hands as described by Part 2.
-    res.sort_by(|one, two| {
-        one.joker_hand_type()
-            .cmp(&two.joker_hand_type())
-            .then(two.cards.cmp(&one.cards))
-    });
-    // Reverse the cards.
-    res.reverse();
-
-    // Sum the ranks
-    res.iter()
-        .enumerate()
-        .map(|(idx, hand)| (idx + 1) * (hand.bid as usize))
-        .sum()
+/// Print a report of every hand, as requested by the
+/// arguments following `report` on the command line.
+///
+/// The options are `--jokers` to rank using the rules
+/// from part two, `--sort <line|rank|bid|winnings>`, and
+/// `--type <hand type>` to only show one type of hand.
+fn print_report(data: &[Hand], args: &[String]) -> Result<(), ParseError> {
+    let mut rules = Rules::standard();
+    let mut sort = SortKey::Line;
+    let mut filter = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--jokers" => rules = Rules::jokers(),
+            "--sort" => {
+                let Some(key) = args.next() else {
+                    return Err(ParseError::InvalidFormat("sort key"));
+                };
+                sort = key.parse()?;
+            }
+            "--type" => {
+                let Some(kind) = args.next() else {
+                    return Err(ParseError::InvalidFormat("hand type"));
+                };
+                filter = Some(kind.parse::<HandType>()?);
+            }
+            _ => return Err(ParseError::InvalidFormat("known report option")),
+        }
+    }
+
+    let mut reports = report(data, &rules);
+    match sort {
+        SortKey::Line => {}
+        SortKey::Rank => reports.sort_by_key(|report| report.rank),
+        SortKey::Bid => reports.sort_by_key(|report| report.hand.bid),
+        SortKey::Winnings => reports.sort_by_key(|report| report.winnings),
+    }
+
+    let total: usize = reports.iter().map(|report| report.winnings).sum();
+    for report in reports.iter() {
+        let joker_type = if rules.wild.is_empty() {
+            report.hand_type
+        } else {
+            report.joker_type
+        };
+        if filter.is_none_or(|kind| kind == joker_type) {
+            println!("{}", report);
+        }
+    }
+    println!("Total winnings are {}", total);
+    Ok(())
 }
 
 fn main() {
     let input = read_to_string("src/input/day07.txt").expect("Could not read input");
     let data = parse_input(&input).expect("Parsing failed");
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "report") {
+        print_report(&data, &args[1..]).expect("Invalid report options");
+        return;
+    }
+
     println!("Total winnings are {}", part_one(&data));
     println!("Total winnings with jokers are {}", part_two(&data));
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
+
     use super::*;
 
+    /// Find the best type for a hand the slow way, by turning
+    /// each wild card into every possible card and keeping the
+    /// best result. This is what `joker_hand_type` is shortcutting.
+    fn brute_force_hand_type(hand: &Hand, rules: &Rules) -> HandType {
+        let wilds: Vec<_> = (0..5)
+            .filter(|&idx| rules.is_wild(hand.cards[idx]))
+            .collect();
+        (0..13usize.pow(wilds.len() as u32))
+            .map(|mut choice| {
+                let mut cards = hand.cards;
+                // Treat the choice as a base 13 number,
+                // with a digit for each of the wild cards.
+                for &idx in wilds.iter() {
+                    cards[idx] = CARDS[choice % 13];
+                    choice /= 13;
+                }
+                Hand { bid: 0, cards }.hand_type()
+            })
+            .min()
+            .expect("There is always at least one choice")
+    }
+
     #[test]
     fn test_parse_input() {
         let example = "AT769 32";
@@ -420,4 +736,102 @@ JJJJ2 41";
 
         assert_eq!(part_two(&data), 6839);
     }
+
+    #[test]
+    fn test_multiple_wilds() {
+        let rules = Rules {
+            order: CARDS,
+            wild: vec![1, JOKER],
+            tie_break: TieBreak::FirstCard,
+        };
+        let data = parse_input("2345J 1\n22JJ2 2\nA2KQT 3").expect("Data failed to parse");
+
+        assert_eq!(rules.hand_type(&data[0]), HandType::ThreeOfKind);
+        assert_eq!(rules.hand_type(&data[1]), HandType::FiveOfKind);
+        assert_eq!(rules.hand_type(&data[2]), HandType::OnePair);
+    }
+
+    #[test]
+    fn test_rules_new() {
+        let mut reversed = CARDS;
+        reversed.reverse();
+        let rules = Rules::new(reversed, vec![], TieBreak::FirstCard).expect("Rules are valid");
+        let data = parse_input("23456 1\nAKQJT 2").expect("Data failed to parse");
+
+        // Twos are high now, so the first hand wins.
+        assert_eq!(rules.total_winnings(&data), 2 + 2);
+
+        let mut repeated = CARDS;
+        repeated[0] = 13;
+        assert_eq!(
+            Rules::new(repeated, vec![], TieBreak::FirstCard),
+            Err(RulesError::MissingCard(1))
+        );
+        assert_eq!(
+            Rules::new(CARDS, vec![14], TieBreak::Poker),
+            Err(RulesError::UnknownWild(14))
+        );
+    }
+
+    #[test]
+    fn test_poker_tie_break() {
+        let mut rules = Rules::standard();
+        let data = parse_input("22333 1\n33222 2").expect("Data failed to parse");
+
+        assert_eq!(rules.total_winnings(&data), 5);
+
+        rules.tie_break = TieBreak::Poker;
+        assert_eq!(rules.total_winnings(&data), 4);
+    }
+
+    #[test]
+    fn test_report() {
+        let example = read_to_string("src/input/day07-test.txt").expect("Could not read example");
+        let data = parse_input(&example).expect("Data failed to parse");
+
+        let reports = report(&data, &Rules::jokers());
+        let total: usize = reports.iter().map(|report| report.winnings).sum();
+        assert_eq!(total, 5905);
+
+        let kk677 = &reports[2];
+        assert_eq!(kk677.hand.to_string(), "KK677");
+        assert_eq!(kk677.hand_type, HandType::TwoPair);
+        assert_eq!(kk677.joker_type, HandType::TwoPair);
+        assert_eq!(kk677.joker_target, None);
+        assert_eq!(kk677.rank, 2);
+
+        let qqqja = &reports[4];
+        assert_eq!(qqqja.hand_type, HandType::ThreeOfKind);
+        assert_eq!(qqqja.joker_type, HandType::FourOfKind);
+        assert_eq!(qqqja.joker_target.map(card_label), Some('Q'));
+        assert_eq!(qqqja.rank, 4);
+    }
+
+    #[test]
+    fn test_joker_hand_type_exhaustive() {
+        let rules = Rules::jokers();
+        // The type only depends on which cards are in the hand,
+        // so remember the brute force results for each set of cards.
+        let mut known = HashMap::new();
+        for mut idx in 0..13usize.pow(5) {
+            let mut cards = [0; 5];
+            for card in cards.iter_mut() {
+                *card = CARDS[idx % 13];
+                idx /= 13;
+            }
+            let hand = Hand { bid: 0, cards };
+            let mut sorted = cards;
+            sorted.sort();
+            let expected = *known
+                .entry(sorted)
+                .or_insert_with(|| brute_force_hand_type(&hand, &rules));
+
+            assert_eq!(
+                hand.joker_hand_type(&rules),
+                expected,
+                "Mismatch for {}",
+                hand
+            );
+        }
+    }
 }
@@ -8,10 +8,12 @@
 //! lost. Our only hope comes in the form of...
 //! a linked list. WONDERFUL.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::fs::read_to_string;
+use std::str::FromStr;
 
 use advent_2023::ParseError;
+use thiserror::Error;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 /// An enum to represent what could
@@ -86,6 +88,271 @@ fn parse_input(input: &str) -> Result<Map, ParseError> {
     })
 }
 
+#[derive(Clone, Debug, PartialEq, Error)]
+/// Something wrong with the shape of the network.
+enum GraphError {
+    #[error("Paths lead to nodes that don't exist: {}", .0.join(", "))]
+    /// Some nodes are pointed to but never described.
+    DanglingNodes(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A set of nodes, stored one bit per node.
+struct NodeSet(Vec<u64>);
+
+impl NodeSet {
+    /// Make a set that can hold nodes `0..size`,
+    /// containing the nodes that match `pred`.
+    fn from_fn(size: usize, pred: impl Fn(usize) -> bool) -> NodeSet {
+        let mut set = NodeSet(vec![0; size.div_ceil(64)]);
+        for node in (0..size).filter(|&node| pred(node)) {
+            set.0[node / 64] |= 1 << (node % 64);
+        }
+        set
+    }
+
+    /// Check whether a node is in the set.
+    fn contains(&self, node: usize) -> bool {
+        self.0[node / 64] & (1 << (node % 64)) != 0
+    }
+
+    /// Get every node in the set, smallest first.
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(block, &bits)| {
+            (0..64)
+                .filter(move |bit| bits & (1 << bit) != 0)
+                .map(move |bit| block * 64 + bit)
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+/// A way of picking out nodes by name.
+///
+/// Written on the command line as the name itself for
+/// an exact match, `*Z` to match names ending in `Z`,
+/// `Z*` to match names starting with `Z`, and anything
+/// else using `*` and `?` is matched as a glob.
+enum Pattern {
+    /// The name must be exactly this.
+    Exact(String),
+    /// The name must start with this.
+    Prefix(String),
+    /// The name must end with this.
+    Suffix(String),
+    /// The name must match this, where `*` matches any
+    /// number of characters and `?` matches exactly one.
+    Glob(String),
+}
+
+impl Pattern {
+    /// Check whether a node name matches the pattern.
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Pattern::Exact(exact) => name == exact,
+            Pattern::Prefix(prefix) => name.starts_with(prefix.as_str()),
+            Pattern::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            Pattern::Glob(glob) => {
+                let glob: Vec<_> = glob.chars().collect();
+                let name: Vec<_> = name.chars().collect();
+                glob_matches(&glob, &name)
+            }
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let is_literal = |s: &str| !s.is_empty() && !s.contains(['*', '?']);
+        if s.is_empty() {
+            Err(ParseError::InvalidFormat("non-empty pattern"))
+        } else if is_literal(s) {
+            Ok(Pattern::Exact(s.to_string()))
+        } else if let Some(suffix) = s.strip_prefix('*').filter(|rest| is_literal(rest)) {
+            Ok(Pattern::Suffix(suffix.to_string()))
+        } else if let Some(prefix) = s.strip_suffix('*').filter(|rest| is_literal(rest)) {
+            Ok(Pattern::Prefix(prefix.to_string()))
+        } else {
+            Ok(Pattern::Glob(s.to_string()))
+        }
+    }
+}
+
+/// Check whether a name matches a glob, one character at a time.
+fn glob_matches(glob: &[char], name: &[char]) -> bool {
+    match glob.split_first() {
+        None => name.is_empty(),
+        // A star can either match nothing or eat one more character.
+        Some(('*', rest)) => {
+            glob_matches(rest, name) || (!name.is_empty() && glob_matches(glob, &name[1..]))
+        }
+        Some((&ch, rest)) => match name.split_first() {
+            Some((&first, others)) if ch == '?' || ch == first => glob_matches(rest, others),
+            _ => false,
+        },
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// The map, compiled down into something
+/// that's quick to walk around.
+///
+/// Every node is given a number, and the paths
+/// out of each node are stored in two arrays
+/// indexed by that number, so taking a step is
+/// just a lookup rather than hashing a string.
+struct Graph {
+    /// The list of directions we are to take.
+    directions: Vec<Direction>,
+    /// The name of each node, sorted by name.
+    names: Vec<String>,
+    /// Where the left path of each node leads.
+    left: Vec<usize>,
+    /// Where the right path of each node leads.
+    right: Vec<usize>,
+    /// The nodes where the ghosts start,
+    /// which end with 'A' unless otherwise asked.
+    starts: NodeSet,
+    /// The nodes where the ghosts want to be,
+    /// which end with 'Z' unless otherwise asked.
+    ends: NodeSet,
+}
+
+impl Graph {
+    /// Compile a parsed map, making sure that
+    /// every path leads to a node we know about.
+    fn new(data: &Map) -> Result<Graph, GraphError> {
+        let mut names: Vec<_> = data.network.keys().cloned().collect();
+        names.sort();
+        let ids: HashMap<&str, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.as_str(), id))
+            .collect();
+
+        // Collect every name that a path points to
+        // but that doesn't have a node of its own.
+        let dangling: BTreeSet<_> = data
+            .network
+            .values()
+            .flat_map(|(left, right)| [left, right])
+            .filter(|name| !ids.contains_key(name.as_str()))
+            .cloned()
+            .collect();
+        if !dangling.is_empty() {
+            return Err(GraphError::DanglingNodes(dangling.into_iter().collect()));
+        }
+
+        // Every name is known now, so the lookups can't fail.
+        let (left, right) = names
+            .iter()
+            .map(|name| {
+                let (left, right) = &data.network[name];
+                (ids[left.as_str()], ids[right.as_str()])
+            })
+            .unzip();
+        let starts = NodeSet::from_fn(names.len(), |id| names[id].ends_with('A'));
+        let ends = NodeSet::from_fn(names.len(), |id| names[id].ends_with('Z'));
+
+        Ok(Graph {
+            directions: data.directions.clone(),
+            names,
+            left,
+            right,
+            starts,
+            ends,
+        })
+    }
+
+    /// Pick out different nodes for the ghosts
+    /// to start from and to try to get to.
+    fn with_targets(mut self, starts: &Pattern, ends: &Pattern) -> Graph {
+        self.starts = NodeSet::from_fn(self.names.len(), |id| starts.matches(&self.names[id]));
+        self.ends = NodeSet::from_fn(self.names.len(), |id| ends.matches(&self.names[id]));
+        self
+    }
+
+    /// Look up the number given to a node.
+    fn id(&self, name: &str) -> Option<usize> {
+        self.names
+            .binary_search_by(|other| other.as_str().cmp(name))
+            .ok()
+    }
+
+    /// Take a single step from a node.
+    fn step(&self, node: usize, direction: Direction) -> usize {
+        match direction {
+            Direction::Left => self.left[node],
+            Direction::Right => self.right[node],
+        }
+    }
+
+    /// Find every node that can be reached from one of the
+    /// starting nodes, including the starting nodes themselves.
+    fn reachable(&self) -> NodeSet {
+        let mut seen = vec![false; self.names.len()];
+        let mut queue: Vec<_> = self.starts.iter().collect();
+        while let Some(node) = queue.pop() {
+            if seen[node] {
+                continue;
+            }
+            seen[node] = true;
+            queue.push(self.left[node]);
+            queue.push(self.right[node]);
+        }
+        NodeSet::from_fn(self.names.len(), |node| seen[node])
+    }
+
+    /// Draw the network in Graphviz's DOT language.
+    ///
+    /// Starting nodes are green boxes and ending nodes are red
+    /// double circles. Each path is labelled with the direction
+    /// that takes it, and if both paths out of a node go to the
+    /// same place, they're drawn as a single edge. If `reachable`
+    /// is set, only the nodes the ghosts can actually get to are drawn.
+    fn to_dot(&self, reachable: bool) -> String {
+        let shown = if reachable {
+            self.reachable()
+        } else {
+            NodeSet::from_fn(self.names.len(), |_| true)
+        };
+
+        let mut res = String::from("digraph network {\n");
+        for node in shown.iter() {
+            let name = &self.names[node];
+            if self.starts.contains(node) {
+                res += &format!(
+                    "    \"{}\" [shape=box, style=filled, fillcolor=palegreen];\n",
+                    name
+                );
+            } else if self.ends.contains(node) {
+                res += &format!(
+                    "    \"{}\" [shape=doublecircle, style=filled, fillcolor=lightcoral];\n",
+                    name
+                );
+            }
+        }
+        for node in shown.iter() {
+            let (left, right) = (self.left[node], self.right[node]);
+            let edges = if left == right {
+                vec![(left, "L/R")]
+            } else {
+                vec![(left, "L"), (right, "R")]
+            };
+            for (target, label) in edges {
+                res += &format!(
+                    "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                    self.names[node], self.names[target], label
+                );
+            }
+        }
+        res += "}\n";
+        res
+    }
+}
+
 /// Part 1
 /// ------
 ///
@@ -93,11 +360,12 @@ fn parse_input(input: &str) -> Result<Map, ParseError> {
 /// how many steps does it take to get from
 /// point "AAA" to point "ZZZ"?
 ///
-/// Returns `None` if the network traversal
-/// encounters an error.
-fn part_one(data: &Map) -> Option<usize> {
+/// Returns `None` if either of
+/// those nodes doesn't exist.
+fn part_one(data: &Graph) -> Option<usize> {
     // The current node.
-    let mut curr = &String::from("AAA");
+    let mut curr = data.id("AAA")?;
+    let goal = data.id("ZZZ")?;
     // How many steps we've taken.
     let mut steps = 0;
 
@@ -105,21 +373,14 @@ fn part_one(data: &Map) -> Option<usize> {
     // we infinitely loop them here. This means
     // that the `break` is entirely vital, since
     // without it, this would loop forever.
-    for direction in data.directions.iter().cycle() {
-        // Check that there's a node here, otherwise fail.
-        let Some(node) = data.network.get(curr) else {
-            return None;
-        };
+    for &direction in data.directions.iter().cycle() {
         // Replace the current node with the one down the path.
-        curr = match *direction {
-            Direction::Left => &node.0,
-            Direction::Right => &node.1,
-        };
+        curr = data.step(curr, direction);
         // Increment the step counter
         // BEFORE we check the destination.
         steps += 1;
         // Are we there yet?
-        if *curr == "ZZZ" {
+        if curr == goal {
             break;
         }
     }
@@ -127,6 +388,107 @@ fn part_one(data: &Map) -> Option<usize> {
     Some(steps)
 }
 
+#[derive(Clone, Debug, PartialEq)]
+/// Every step at which a single ghost is standing on a Z node.
+///
+/// Since there are only so many combinations of node and
+/// position in the directions, every walk eventually falls
+/// into a loop. The walk is made up of a prefix of `start`
+/// steps which are only ever seen once, followed by a cycle
+/// of `period` steps that repeats forever.
+struct Schedule {
+    /// The steps before the cycle starts that land on a Z node.
+    prefix: Vec<u64>,
+    /// How many steps it takes to enter the cycle.
+    start: u64,
+    /// How many steps it takes to go around the cycle once.
+    period: u64,
+    /// The steps in the first trip around the cycle
+    /// that land on a Z node, from `start` up to but not
+    /// including `start + period`. Each of these happens
+    /// again every `period` steps after that.
+    offsets: Vec<u64>,
+}
+
+impl Schedule {
+    /// Walk the network from `start` until the walk starts
+    /// repeating itself, taking note of every Z node on the way.
+    ///
+    /// Returns `None` if there are no directions to follow.
+    fn new(data: &Graph, start: usize) -> Option<Schedule> {
+        if data.directions.is_empty() {
+            return None;
+        }
+        // When we were last at each node right at the
+        // start of the directions. The walk has to loop
+        // back to one of these within one pass per node.
+        let mut seen = vec![None; data.names.len()];
+        let mut hits = vec![];
+        let mut curr = start;
+        let mut steps = 0;
+
+        loop {
+            // If we've been here before, at the start of
+            // the directions, then we've found the loop.
+            if let Some(first) = seen[curr] {
+                let (prefix, cycle): (Vec<_>, Vec<_>) =
+                    hits.into_iter().partition(|&hit| hit < first);
+                let period = steps - first;
+                // The last step of the cycle is back where it
+                // started, so keep every offset in the same range.
+                let mut offsets: Vec<_> = cycle
+                    .into_iter()
+                    .map(|hit| first + (hit - first) % period)
+                    .collect();
+                offsets.sort_unstable();
+                offsets.dedup();
+                return Some(Schedule {
+                    prefix,
+                    start: first,
+                    period,
+                    offsets,
+                });
+            }
+            seen[curr] = Some(steps);
+
+            for &direction in data.directions.iter() {
+                curr = data.step(curr, direction);
+                // Increment the step counter
+                // BEFORE we check for a Z node.
+                steps += 1;
+                if data.ends.contains(curr) {
+                    hits.push(steps);
+                }
+            }
+        }
+    }
+
+    /// Get the first step at which the ghost is on a Z node,
+    /// or `None` if it never gets to one.
+    fn first(&self) -> Option<u64> {
+        // Ghosts always take at least one step, so if the
+        // cycle starts straight away, its first step can
+        // only be reached by going around once.
+        let cycle = || {
+            self.offsets
+                .iter()
+                .map(|&offset| if offset == 0 { self.period } else { offset })
+                .min()
+        };
+        self.prefix.first().copied().or_else(cycle)
+    }
+
+    /// Check whether the ghost is on a Z node after `step` steps.
+    fn contains(&self, step: u64) -> bool {
+        if step < self.start {
+            self.prefix.contains(&step)
+        } else {
+            let offset = (step - self.start) % self.period;
+            self.offsets.contains(&(self.start + offset))
+        }
+    }
+}
+
 /// Part 2
 /// ------
 ///
@@ -136,98 +498,199 @@ fn part_one(data: &Map) -> Option<usize> {
 /// '??A' nodes to '??Z' nodes. How long
 /// until we get from all of the A nodes
 /// to only Z nodes?
-fn part_two(data: &Map) -> Option<u64> {
-    // Get the list of A nodes.
-    let curr = data
-        .network
-        .keys()
-        .filter(|name| name.ends_with('A'))
-        .collect::<Vec<_>>();
-
-    // Right, so this is a BIG hack.
-    // TODO: Find a solution that doesn't rely on unspoken assumptions.
-    //
-    // Every path from an A node to a Z node forms a cycle.
-    // If we can find all such cycles, then the number
-    // of steps the problem requires is just the least common
-    // multiple of the lengths of each cycle.
-    //
-    // So we just need to find the cycles.
-    let res = curr
+///
+/// Returns `None` if the ghosts never line up.
+fn part_two(data: &Graph) -> Option<u64> {
+    navigate(data)?.combined
+}
+
+/// Find the first step at which every ghost
+/// is on a Z node at the same time.
+///
+/// Returns `None` if the ghosts never line up.
+fn combine(schedules: &[Schedule]) -> Option<u64> {
+    // The inputs for the puzzle happen to be set up so that
+    // every ghost hits exactly one Z node right at the end of
+    // its cycle, which means the answer is just the least
+    // common multiple of the cycle lengths. We can't count
+    // on that in general, so we have to do it properly.
+    let Some((first, rest)) = schedules.split_first() else {
+        // No ghosts means we're already done.
+        return Some(0);
+    };
+
+    // Until every ghost has made it into its cycle, just
+    // check the Z nodes the first ghost lands on one by one.
+    let settled = schedules.iter().map(|schedule| schedule.start).max()?;
+    let early = (1..settled)
+        .filter(|&step| first.contains(step))
+        .find(|&step| rest.iter().all(|schedule| schedule.contains(step)));
+    if early.is_some() {
+        return early;
+    }
+
+    // After that, every ghost is going around its cycle, so each
+    // one is on a Z node exactly when the step count is one of its
+    // offsets, modulo its period. Find every combination of offsets
+    // that is possible with the Chinese Remainder Theorem.
+    let congruences = schedules
         .iter()
-        .map(|&name| {
-            let mut curr = &name.clone();
-            let mut steps = 0;
-
-            // Again, we loop the directions infinitely
-            // in case we run out midway through.
-            for direction in data.directions.iter().cycle() {
-                // Check that the node we're at actually exists.
-                let Some(node) = data.network.get(curr) else {
-                    return None;
-                };
-                // Replace the node we're at
-                // with the node we're going to.
-                curr = match *direction {
-                    Direction::Left => &node.0,
-                    Direction::Right => &node.1,
-                };
-                // Increment the step counter
-                // BEFORE we consider ending the loop.
-                steps += 1;
-                // Check if we're at a Z node.
-                if curr.ends_with('Z') {
-                    break;
+        .fold(vec![(0, 1)], |congruences, schedule| {
+            let mut res = vec![];
+            for &(residue, modulus) in congruences.iter() {
+                for &offset in schedule.offsets.iter() {
+                    let combined = crt(residue, modulus, offset % schedule.period, schedule.period);
+                    if let Some(combined) = combined {
+                        if !res.contains(&combined) {
+                            res.push(combined);
+                        }
+                    }
                 }
             }
+            res
+        });
 
-            Some(steps)
+    // The answer is the first step after everyone has
+    // settled into their cycles that works for everyone.
+    // Ghosts have to take at least one step, even if
+    // they all start on Z nodes, so step 0 doesn't count.
+    congruences
+        .into_iter()
+        .filter_map(|(residue, modulus)| {
+            let settled = u128::from(settled.max(1));
+            let step = if residue >= settled {
+                residue
+            } else {
+                residue + (settled - residue).div_ceil(modulus) * modulus
+            };
+            u64::try_from(step).ok()
         })
-        // Remove any elements that failed to navigate.
-        .flatten()
-        .collect::<Vec<u64>>();
+        .min()
+}
 
-    // Double check that every node navigated correctly.
-    if res.len() != curr.len() {
-        None
-    } else {
-        // Get the least common multiple of all of the cycles.
-        //
-        // This SHOULD be a call to `reduce`, but the lifetimes
-        // were too much of a mess for me to want to put up with.
-        Some(res.iter().fold(1, |one, two| lcm(one, *two)))
-    }
+#[derive(Clone, Debug, PartialEq)]
+/// How the ghosts got on when navigating the network.
+struct Navigation {
+    /// The name of each starting node, along with the first
+    /// step at which that ghost alone reaches a goal node.
+    ghosts: Vec<(String, Option<u64>)>,
+    /// The first step at which every ghost is on a goal node.
+    combined: Option<u64>,
 }
 
-/// Greatest common divisor
+/// Send a ghost out from every starting node in the graph,
+/// and see when they get to the goal nodes.
 ///
-/// This is kind of a dumb but
-/// simple way to go about it,
-/// but it certainly works.
-fn gcd(a: u64, b: u64) -> u64 {
-    let mut one = a;
-    let mut two = b;
+/// Returns `None` if there are no directions to follow.
+fn navigate(data: &Graph) -> Option<Navigation> {
+    let schedules = data
+        .starts
+        .iter()
+        .map(|start| Some((start, Schedule::new(data, start)?)))
+        .collect::<Option<Vec<_>>>()?;
 
-    while two != 0 {
-        let rem = one % two;
-        one = two;
-        two = rem;
+    let ghosts = schedules
+        .iter()
+        .map(|(start, schedule)| (data.names[*start].clone(), schedule.first()))
+        .collect();
+    let schedules: Vec<_> = schedules
+        .into_iter()
+        .map(|(_, schedule)| schedule)
+        .collect();
+
+    Some(Navigation {
+        ghosts,
+        combined: combine(&schedules),
+    })
+}
+
+/// Chinese Remainder Theorem
+///
+/// Find the numbers that are congruent to `one` modulo
+/// `one_mod` and to `two` modulo `two_mod`. This works even
+/// if the moduli aren't coprime, in which case there might
+/// not be any such number. Otherwise, the numbers are all
+/// congruent to the returned residue modulo the returned modulus.
+fn crt(one: u128, one_mod: u128, two: u64, two_mod: u64) -> Option<(u128, u128)> {
+    let two = i128::from(two);
+    let two_mod = i128::from(two_mod);
+    let one = i128::try_from(one).ok()?;
+    let one_mod = i128::try_from(one_mod).ok()?;
+
+    let (divisor, inverse, _) = extended_gcd(one_mod, two_mod);
+    // Both congruences have to agree modulo
+    // the divisor, or there are no solutions.
+    if (two - one) % divisor != 0 {
+        return None;
     }
+    let modulus = one_mod.checked_mul(two_mod / divisor)?;
+    // Step up from `one` in multiples of `one_mod`
+    // until we hit something congruent to `two`.
+    let reduced = two_mod / divisor;
+    let factor = ((two - one) / divisor % reduced * inverse).rem_euclid(reduced);
+    let residue = (one + one_mod.checked_mul(factor)?).rem_euclid(modulus);
 
-    one
+    Some((residue as u128, modulus as u128))
 }
 
-/// Least common multiple
+/// Extended Euclidean algorithm
 ///
-/// The multiplication here is why Part 2
-/// returns a `u64`; it overflowed on `u32`.
-fn lcm(one: u64, two: u64) -> u64 {
-    (one * two) / gcd(one, two)
+/// Returns the greatest common divisor of `a` and `b`,
+/// along with `x` and `y` such that `a * x + b * y`
+/// is equal to the divisor.
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (divisor, x, y) = extended_gcd(b, a % b);
+        (divisor, y, x - (a / b) * y)
+    }
 }
 
 fn main() {
     let input = read_to_string("src/input/day08.txt").expect("Could not read data");
     let data = parse_input(&input).expect("Parsing failed");
+    let data = Graph::new(&data).expect("Network must be complete");
+
+    // Print the network for Graphviz instead of solving, if asked.
+    // Passing `--reachable` leaves out nodes no ghost can get to.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "dot") {
+        let reachable = args.iter().any(|arg| arg == "--reachable");
+        print!("{}", data.to_dot(reachable));
+        return;
+    }
+
+    // Navigate between any nodes we like, if asked. The start and
+    // goal nodes are given as patterns with `--from` and `--to`.
+    if args.first().is_some_and(|arg| arg == "navigate") {
+        let mut from = Pattern::Suffix("A".to_string());
+        let mut to = Pattern::Suffix("Z".to_string());
+        let mut args = args[1..].iter();
+        while let Some(arg) = args.next() {
+            let pattern = args.next().expect("Option needs a pattern");
+            let pattern = pattern.parse().expect("Pattern should be valid");
+            match arg.as_str() {
+                "--from" => from = pattern,
+                "--to" => to = pattern,
+                _ => panic!("Unknown option {}", arg),
+            }
+        }
+
+        let data = data.with_targets(&from, &to);
+        let res = navigate(&data).expect("There should be directions to follow");
+        for (name, steps) in res.ghosts.iter() {
+            match steps {
+                Some(steps) => println!("{} reaches a goal after {} steps", name, steps),
+                None => println!("{} never reaches a goal", name),
+            }
+        }
+        match res.combined {
+            Some(steps) => println!("Every ghost is on a goal after {} steps", steps),
+            None => println!("The ghosts are never all on a goal at once"),
+        }
+        return;
+    }
 
     let steps = part_one(&data).expect("Network traversal failed");
     println!("Number of steps from AAA to ZZZ is {}", steps);
@@ -267,6 +730,7 @@ mod test {
     fn test_part_one() {
         let input = read_to_string("src/input/day08-test.txt").expect("Could not read example");
         let data = parse_input(&input).expect("Parsing failed");
+        let data = Graph::new(&data).expect("Network should be complete");
 
         assert_eq!(
             part_one(&data),
@@ -275,5 +739,191 @@ mod test {
         );
     }
 
-    // No test for part two here because it would require another input file.
+    // The example for part two is different from the
+    // example for part one, so it gets its own copy here.
+    const GHOST_EXAMPLE: &str = "LR
+
+11A = (11B, XXX)
+11B = (XXX, 11Z)
+11Z = (11B, XXX)
+22A = (22B, XXX)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)
+XXX = (XXX, XXX)";
+
+    #[test]
+    fn test_part_two() {
+        let data = parse_input(GHOST_EXAMPLE).expect("Parsing failed");
+        let data = Graph::new(&data).expect("Network should be complete");
+
+        assert_eq!(part_two(&data), Some(6));
+    }
+
+    #[test]
+    fn test_part_two_offset_cycles() {
+        // The first ghost lands on a Z node every odd step,
+        // while the second lands on one at steps 2, 5, 8...
+        // The least common multiple trick would say 2.
+        let input = "L
+
+1A = (1Z, 1Z)
+1Z = (1B, 1B)
+1B = (1Z, 1Z)
+2A = (2B, 2B)
+2B = (2Z, 2Z)
+2Z = (2C, 2C)
+2C = (2B, 2B)";
+        let data = parse_input(input).expect("Parsing failed");
+        let data = Graph::new(&data).expect("Network should be complete");
+
+        assert_eq!(part_two(&data), Some(5));
+    }
+
+    #[test]
+    fn test_part_two_never_aligned() {
+        // Even steps for the first ghost, odd for the second.
+        let input = "L
+
+1A = (1B, 1B)
+1B = (1Z, 1Z)
+1Z = (1B, 1B)
+2A = (2Z, 2Z)
+2Z = (2B, 2B)
+2B = (2Z, 2Z)";
+        let data = parse_input(input).expect("Parsing failed");
+        let data = Graph::new(&data).expect("Network should be complete");
+
+        assert_eq!(part_two(&data), None);
+    }
+
+    #[test]
+    fn test_graph() {
+        let data = parse_input(GHOST_EXAMPLE).expect("Parsing failed");
+        let graph = Graph::new(&data).expect("Network should be complete");
+
+        let start = graph.id("22A").expect("22A should be in the graph");
+        assert_eq!(graph.starts.iter().collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(graph.step(start, Direction::Left), graph.id("22B").unwrap());
+        assert!(graph.ends.contains(graph.id("11Z").unwrap()));
+        assert!(!graph.ends.contains(start));
+    }
+
+    #[test]
+    fn test_dangling_nodes() {
+        let data = parse_input("L\n\nAAA = (BBB, CCC)\nBBB = (DDD, AAA)").expect("Parsing failed");
+
+        assert_eq!(
+            Graph::new(&data),
+            Err(GraphError::DanglingNodes(vec![
+                "CCC".to_string(),
+                "DDD".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        let input = "L
+
+AAA = (BBB, BBB)
+BBB = (AAA, ZZZ)
+ZZZ = (ZZZ, ZZZ)
+QQQ = (ZZZ, AAA)";
+        let data = parse_input(input).expect("Parsing failed");
+        let graph = Graph::new(&data).expect("Network should be complete");
+
+        let expected = r#"digraph network {
+    "AAA" [shape=box, style=filled, fillcolor=palegreen];
+    "ZZZ" [shape=doublecircle, style=filled, fillcolor=lightcoral];
+    "AAA" -> "BBB" [label="L/R"];
+    "BBB" -> "AAA" [label="L"];
+    "BBB" -> "ZZZ" [label="R"];
+    "ZZZ" -> "ZZZ" [label="L/R"];
+}
+"#;
+        assert_eq!(graph.to_dot(true), expected);
+        assert!(graph
+            .to_dot(false)
+            .contains(r#""QQQ" -> "ZZZ" [label="L"];"#));
+    }
+
+    #[test]
+    fn test_pattern() {
+        assert_eq!("AAA".parse(), Ok(Pattern::Exact("AAA".to_string())));
+        assert_eq!("*Z".parse(), Ok(Pattern::Suffix("Z".to_string())));
+        assert_eq!("11*".parse(), Ok(Pattern::Prefix("11".to_string())));
+        assert_eq!("?2*".parse(), Ok(Pattern::Glob("?2*".to_string())));
+
+        let glob = Pattern::Glob("?2*Z".to_string());
+        assert!(glob.matches("22Z"));
+        assert!(glob.matches("12ABZ"));
+        assert!(!glob.matches("22A"));
+        assert!(!glob.matches("2Z"));
+    }
+
+    #[test]
+    fn test_navigate() {
+        let data = parse_input(GHOST_EXAMPLE).expect("Parsing failed");
+        let graph = Graph::new(&data).expect("Network should be complete");
+
+        let res = navigate(&graph).expect("Directions should not be empty");
+        assert_eq!(
+            res.ghosts,
+            vec![("11A".to_string(), Some(2)), ("22A".to_string(), Some(3))]
+        );
+        assert_eq!(res.combined, Some(6));
+
+        let graph = graph.with_targets(&"22A".parse().unwrap(), &"22?".parse().unwrap());
+        let res = navigate(&graph).expect("Directions should not be empty");
+        assert_eq!(res.ghosts, vec![("22A".to_string(), Some(1))]);
+        assert_eq!(res.combined, Some(1));
+    }
+
+    #[test]
+    fn test_navigate_starting_on_goals() {
+        let data = parse_input("L\n\nAZ = (AZ, AZ)\n").expect("Parsing failed");
+        let graph = Graph::new(&data).expect("Network should be complete");
+        let graph = graph.with_targets(&"*Z".parse().unwrap(), &"*Z".parse().unwrap());
+
+        let res = navigate(&graph).expect("Directions should not be empty");
+        assert_eq!(res.ghosts, vec![("AZ".to_string(), Some(1))]);
+        assert_eq!(res.combined, Some(1));
+
+        let data = parse_input("L\n\n1A = (1B, 1B)\n1B = (1A, 1A)\n").expect("Parsing failed");
+        let graph = Graph::new(&data).expect("Network should be complete");
+        let graph = graph.with_targets(&"*".parse().unwrap(), &"*".parse().unwrap());
+
+        let res = navigate(&graph).expect("Directions should not be empty");
+        assert_eq!(
+            res.ghosts,
+            vec![("1A".to_string(), Some(1)), ("1B".to_string(), Some(1))]
+        );
+        assert_eq!(res.combined, Some(1));
+
+        // SZ is a goal at step 0 and loops back every two steps,
+        // while SB only gets stuck on a goal after two steps.
+        let data = parse_input(
+            "L
+
+SZ = (XY, XY)
+XY = (SZ, SZ)
+SB = (B1, B1)
+B1 = (BZ, BZ)
+BZ = (B3, B3)
+B3 = (B4, B4)
+B4 = (B4, B4)
+",
+        )
+        .expect("Parsing failed");
+        let graph = Graph::new(&data).expect("Network should be complete");
+        let graph = graph.with_targets(&"S*".parse().unwrap(), &"*Z".parse().unwrap());
+
+        let res = navigate(&graph).expect("Directions should not be empty");
+        assert_eq!(
+            res.ghosts,
+            vec![("SB".to_string(), Some(2)), ("SZ".to_string(), Some(2))]
+        );
+        assert_eq!(res.combined, Some(2));
+    }
 }
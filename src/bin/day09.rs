@@ -9,118 +9,184 @@
 use std::fs::read_to_string;
 
 use advent_2023::ParseError;
+use thiserror::Error;
+
+#[derive(Clone, Debug, PartialEq, Error)]
+/// The ways that extrapolating a history can go wrong.
+enum HistoryError {
+    #[error("History is empty")]
+    /// There's nothing to extrapolate from.
+    Empty,
+    #[error("History never reduces to all zeroes")]
+    /// The differences ran out before they all became zero,
+    /// so the history isn't a polynomial we can continue.
+    NotPolynomial,
+    #[error("Value is too large for 64 bits")]
+    /// The arithmetic overflowed.
+    Overflow,
+}
+
+#[derive(Clone, Debug, PartialEq, Error)]
+#[error("History on line {line} failed: {error}")]
+/// An error with one particular history in the report.
+struct ReportError {
+    /// Which line of the input the history is on.
+    line: usize,
+    /// What went wrong with it.
+    #[source]
+    error: HistoryError,
+}
 
 #[derive(Clone, Debug, PartialEq)]
 /// A sequence provided in the OASIS report.
-struct History(Vec<i32>);
+struct History(Vec<i64>);
 
 impl History {
-    /// Find the next item in this sequence.
-    fn next(&self) -> i32 {
-        // Check that we don't have an empty sequence.
-        //
-        // We don't know the length of the sequence
-        // off the top of our heads, and Rust only
-        // gives us the last element if the sequence
-        // isn't empty, so this is mainly for waving
-        // off a pathological edge case. Thanks, Rust!
-        let Some(&last) = self.0.last() else {
-            unreachable!("Invalid input received");
-        };
-
-        // We need to collect all of the final
-        // elements we get from this process.
-        // Start with the last element of the
-        // sequence we were originally given.
-        let mut last = vec![last];
-        // This sequence contains the current
-        // stage of the reduction process.
-        // Every time we do the process, we come
-        // out with one element less. We can
-        // only hope that we get to all zeroes
-        // before we get to only one element.
-        let mut diff = self.0.clone();
+    /// Take differences of this history until they're either all
+    /// zeroes or there's only one element left, whichever comes first.
+    ///
+    /// The first row is the history itself, and each row after
+    /// that is the differences between consecutive elements of
+    /// the row above it. This doesn't care whether the history
+    /// is a polynomial, so it's handy for seeing what went wrong.
+    fn differences(&self) -> Result<Vec<Vec<i64>>, HistoryError> {
+        if self.0.is_empty() {
+            return Err(HistoryError::Empty);
+        }
+
+        let mut rows = vec![self.0.clone()];
         loop {
+            let last = rows.last().expect("There is always at least one row");
             // Check if we've done all of the
             // differentiation we can.
-            if diff.iter().all(|&val| val == 0) {
-                break;
+            if last.len() == 1 || last.iter().all(|&val| val == 0) {
+                return Ok(rows);
             }
 
-            diff = diff
+            let diff = last
                 // Get pairs of consecutive elements.
                 .windows(2)
                 // Get the difference of each pair.
-                .map(|window| window[1] - window[0])
-                // Return this to Vec so we can
-                // assign it to our "accumulator".
-                .collect();
-
-            // Pathological safety check to see that
-            // we didn't wind up with an empty sequence.
-            let Some(&new_last) = diff.last() else {
-                unreachable!("History should not reduce to empty");
-            };
-
-            // Add the last item of this stage
-            // to the list to sum up later.
-            last.push(new_last);
+                .map(|window| {
+                    window[1]
+                        .checked_sub(window[0])
+                        .ok_or(HistoryError::Overflow)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            rows.push(diff);
         }
+    }
 
-        // Sum up the last value
-        // of every stage that
-        // we have processed.
-        last.iter().sum()
+    /// Build the triangle of differences for this history.
+    ///
+    /// This is the same as `differences`, except that the
+    /// last row must be all zeroes. If we run out of elements
+    /// before that happens, the history isn't a polynomial
+    /// (or at least, there isn't enough of it to tell).
+    fn triangle(&self) -> Result<Vec<Vec<i64>>, HistoryError> {
+        let rows = self.differences()?;
+        let last = rows.last().expect("There is always at least one row");
+        if last.iter().all(|&val| val == 0) {
+            Ok(rows)
+        } else {
+            Err(HistoryError::NotPolynomial)
+        }
     }
 
-    /// Find the hypothetical previous number in this sequence.
+    /// Draw the differences of this history the
+    /// way the puzzle does, one row under another.
+    fn render_triangle(&self) -> Result<String, HistoryError> {
+        let rows = self.differences()?;
+        // Give every number the same amount of room,
+        // with space for the rows to be staggered.
+        let width = rows
+            .iter()
+            .flatten()
+            .map(|val| val.to_string().len())
+            .max()
+            .unwrap_or(1)
+            + 2;
+        let width = width + width % 2;
+
+        let lines: Vec<_> = rows
+            .iter()
+            .enumerate()
+            .map(|(depth, row)| {
+                let values: String = row.iter().map(|val| format!("{:>width$}", val)).collect();
+                format!("{}{}", " ".repeat(depth * width / 2), values)
+            })
+            .collect();
+        Ok(lines.join("\n"))
+    }
+
+    /// Get the degree of the polynomial that this history follows.
     ///
-    /// So I tried two implementations of this, both of which
-    /// passed the test on the sample input. The one commented
-    /// out calculates the values by hand, using a similar
-    /// logic to the `next` function. For some reason, this
-    /// gives me an answer that is way off and NEGATIVE,
-    /// which AoC doesn't seem suited to handle oddly enough.
+    /// A constant sequence has degree zero, an arithmetic
+    /// sequence has degree one, and so on.
+    fn degree(&self) -> Result<usize, HistoryError> {
+        // The last row is all zeroes, and the one before that is
+        // constant, which is the degree zero case. The history
+        // itself is all zeroes too.
+        Ok(self.triangle()?.len().saturating_sub(2))
+    }
+
+    /// Find the value at any position in the sequence, where
+    /// position zero is the first element of the history.
+    /// Positions past the end or before the start (negative
+    /// positions) are extrapolated.
     ///
-    /// The solution that worked reversed the sequence
-    /// in this history and then used `next` directly. That
-    /// gave the solution AoC was looking for.
-    fn prev(&self) -> i32 {
-        // TODO: Find out why this solution didn't work.
-
-        // let Some(&head) = self.0.first() else {
-        //     unreachable!("Invalid input received");
-        // };
-        //
-        // let mut heads = vec![head];
-        // let mut diff = self.0.clone();
-        // loop {
-        //     if diff.iter().all(|&val| val == 0) {
-        //         break;
-        //     }
-        //
-        //     diff = diff
-        //         .windows(2)
-        //         .map(|window| window[1] - window[0])
-        //         .collect();
-        //
-        //     let Some(&new_head) = diff.first() else {
-        //         unreachable!("History should not reduce to empty");
-        //     };
-        //     heads.push(new_head);
-        // }
-        //
-        // heads.iter().fold(0, |acc, val| val - acc)
-
-        // Get the data inside of this struct.
-        let mut data = self.0.clone();
-        // Reverse the data so that the
-        // first element is at the end.
-        data.reverse();
-
-        // Find the "next" element
-        // of the reversed sequence.
-        History(data).next()
+    /// This uses Newton's forward difference formula: the value
+    /// at position `x` is the sum over every row `j` of the
+    /// triangle of the first element of that row times
+    /// `x choose j`. This works for any `x`, so we don't need
+    /// to build the triangle out step by step.
+    fn value_at(&self, position: i128) -> Result<i64, HistoryError> {
+        let triangle = self.triangle()?;
+
+        // The binomial coefficients grow faster than the answer
+        // does, so work in 128 bits and only insist that the
+        // answer itself fits in 64.
+        let mut total: i128 = 0;
+        // `x choose 0` is always one.
+        let mut choose: i128 = 1;
+        for (row, diffs) in triangle.iter().enumerate() {
+            if row > 0 {
+                // `x choose j` is `x choose (j - 1)` times `(x - j + 1) / j`,
+                // and this always divides evenly.
+                let row = row as i128;
+                choose = choose
+                    .checked_mul(position - row + 1)
+                    .ok_or(HistoryError::Overflow)?
+                    / row;
+            }
+            let term = choose
+                .checked_mul(diffs[0].into())
+                .ok_or(HistoryError::Overflow)?;
+            total = total.checked_add(term).ok_or(HistoryError::Overflow)?;
+        }
+
+        i64::try_from(total).map_err(|_| HistoryError::Overflow)
+    }
+
+    /// Find the value `steps` places after the end of this history.
+    fn forward(&self, steps: u64) -> Result<i64, HistoryError> {
+        let last = self.0.len() as i128 - 1;
+        self.value_at(last + i128::from(steps))
+    }
+
+    /// Find the value `steps` places before the start of this history.
+    fn backward(&self, steps: u64) -> Result<i64, HistoryError> {
+        self.value_at(-i128::from(steps))
+    }
+
+    /// Find the next item in this sequence.
+    fn next(&self) -> Result<i64, HistoryError> {
+        self.forward(1)
+    }
+
+    /// Find the hypothetical previous number in this sequence.
+    fn prev(&self) -> Result<i64, HistoryError> {
+        self.backward(1)
     }
 }
 
@@ -134,10 +200,10 @@ fn parse_input(input: &str) -> Result<Vec<History>, ParseError> {
             let history = line
                 .split_whitespace()
                 // Some values are NEGATIVE in this input,
-                // so we're using an `i32`, not a `u32`.
-                .map(|val| val.parse::<i32>().map_err(ParseError::ExpectedNumber))
+                // so we're using an `i64`, not a `u64`.
+                .map(|val| val.parse::<i64>().map_err(ParseError::ExpectedNumber))
                 // Check to see if we have any errors.
-                .collect::<Result<Vec<i32>, _>>()?;
+                .collect::<Result<Vec<i64>, _>>()?;
             // If not, pass off the sequence.
             Ok(History(history))
         })
@@ -155,8 +221,8 @@ fn parse_input(input: &str) -> Result<Vec<History>, ParseError> {
 ///
 /// When we've found out what all of the
 /// sequences should be, we find the sum.
-fn part_one(data: &[History]) -> i32 {
-    data.iter().map(|history| history.next()).sum()
+fn part_one(data: &[History]) -> Result<i64, ReportError> {
+    sum_each(data, History::next)
 }
 
 /// Part 2
@@ -169,16 +235,94 @@ fn part_one(data: &[History]) -> i32 {
 ///
 /// Find the sum of the hypothetical zeroth
 /// entry in each of the provided sequences.
-fn part_two(data: &[History]) -> i32 {
-    data.iter().map(|history| history.prev()).sum()
+fn part_two(data: &[History]) -> Result<i64, ReportError> {
+    sum_each(data, History::prev)
+}
+
+/// Apply a calculation to every history and add up the results,
+/// making sure that neither the calculation nor the sum overflow.
+///
+/// If anything goes wrong, the error says which line it happened on.
+fn sum_each(
+    data: &[History],
+    calc: impl Fn(&History) -> Result<i64, HistoryError>,
+) -> Result<i64, ReportError> {
+    data.iter()
+        .enumerate()
+        .try_fold(0i64, |total, (idx, history)| {
+            let line = idx + 1;
+            let value = calc(history).map_err(|error| ReportError { line, error })?;
+            total.checked_add(value).ok_or(ReportError {
+                line,
+                error: HistoryError::Overflow,
+            })
+        })
 }
 
 fn main() {
     let input = read_to_string("src/input/day09.txt").expect("Could not read data");
     let data = parse_input(&input).expect("Parsing should succeed");
 
-    println!("Sum of next steps is {}", part_one(&data));
-    println!("Sum of hypothetical previous steps is {}", part_two(&data));
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // Look further ahead (and behind) than the puzzle asks, if asked.
+    if args.first().is_some_and(|arg| arg == "ahead") {
+        let steps = args
+            .get(1)
+            .and_then(|steps| steps.parse::<u64>().ok())
+            .expect("Number of steps should be given");
+        for (line, history) in data.iter().enumerate() {
+            let degree = history.degree().expect("History should be a polynomial");
+            let next = history.forward(steps).expect("History should extrapolate");
+            let prev = history.backward(steps).expect("History should extrapolate");
+            println!(
+                "Line {} has degree {}: {} ahead, {} behind",
+                line + 1,
+                degree,
+                next,
+                prev
+            );
+        }
+        let forward = sum_each(&data, |history| history.forward(steps));
+        let backward = sum_each(&data, |history| history.backward(steps));
+        println!(
+            "Sum of values {} steps ahead is {}",
+            steps,
+            forward.expect("Histories should extrapolate")
+        );
+        println!(
+            "Sum of values {} steps behind is {}",
+            steps,
+            backward.expect("Histories should extrapolate")
+        );
+        return;
+    }
+    // Show the differences for one line of the input, if asked.
+    if args.first().is_some_and(|arg| arg == "triangle") {
+        let line = args
+            .get(1)
+            .and_then(|line| line.parse::<usize>().ok())
+            .expect("Line number should be given");
+        let history = line
+            .checked_sub(1)
+            .and_then(|idx| data.get(idx))
+            .expect("Line should be in the input");
+        println!(
+            "{}",
+            history
+                .render_triangle()
+                .expect("Differences should fit in 64 bits")
+        );
+        return;
+    }
+
+    println!(
+        "Sum of next steps is {}",
+        part_one(&data).expect("Histories should extrapolate")
+    );
+    println!(
+        "Sum of hypothetical previous steps is {}",
+        part_two(&data).expect("Histories should extrapolate")
+    );
 }
 
 #[cfg(test)]
@@ -198,8 +342,8 @@ mod test {
         let input = read_to_string("src/input/day09-test.txt").expect("Could not read example");
         let data = parse_input(&input).expect("Example should parse successfully");
 
-        assert_eq!(data[0].next(), 18);
-        assert_eq!(data[1].next(), 28);
+        assert_eq!(data[0].next(), Ok(18));
+        assert_eq!(data[1].next(), Ok(28));
     }
 
     #[test]
@@ -207,9 +351,9 @@ mod test {
         let input = read_to_string("src/input/day09-test.txt").expect("Could not read example");
         let data = parse_input(&input).expect("Example should parse successfully");
 
-        assert_eq!(data[0].prev(), -3);
-        assert_eq!(data[1].prev(), 0);
-        assert_eq!(data[2].prev(), 5);
+        assert_eq!(data[0].prev(), Ok(-3));
+        assert_eq!(data[1].prev(), Ok(0));
+        assert_eq!(data[2].prev(), Ok(5));
     }
 
     #[test]
@@ -217,6 +361,88 @@ mod test {
         let input = read_to_string("src/input/day09-test.txt").expect("Could not read example");
         let data = parse_input(&input).expect("Example should parse successfully");
 
-        assert_eq!(part_two(&data), 2);
+        assert_eq!(part_two(&data), Ok(2));
+    }
+
+    #[test]
+    fn test_history_extrapolate() {
+        let input = read_to_string("src/input/day09-test.txt").expect("Could not read example");
+        let data = parse_input(&input).expect("Example should parse successfully");
+
+        // The second history is the triangular numbers.
+        assert_eq!(data[1].degree(), Ok(2));
+        assert_eq!(data[1].forward(4), Ok(55));
+        assert_eq!(data[1].backward(3), Ok(1));
+        assert_eq!(data[1].forward(1_000_000), Ok(500_006_500_021));
+
+        assert_eq!(data[0].degree(), Ok(1));
+        assert_eq!(data[2].degree(), Ok(3));
+        assert_eq!(History(vec![7, 7]).degree(), Ok(0));
+    }
+
+    #[test]
+    fn test_history_errors() {
+        assert_eq!(History(vec![]).next(), Err(HistoryError::Empty));
+        assert_eq!(History(vec![5]).next(), Err(HistoryError::NotPolynomial));
+        assert_eq!(
+            History(vec![1, 2, 4, 8]).prev(),
+            Err(HistoryError::NotPolynomial)
+        );
+        assert_eq!(History(vec![0]).next(), Ok(0));
+    }
+
+    #[test]
+    fn test_report_errors() {
+        let data = parse_input("1 2 3\n1 2 4 8\n4 4 4").expect("Example should parse successfully");
+        assert_eq!(
+            part_one(&data),
+            Err(ReportError {
+                line: 2,
+                error: HistoryError::NotPolynomial
+            })
+        );
+
+        let data = parse_input("9223372036854775795 9223372036854775800 9223372036854775805\n1 1")
+            .expect("Example should parse successfully");
+        assert_eq!(
+            part_one(&data),
+            Err(ReportError {
+                line: 1,
+                error: HistoryError::Overflow
+            })
+        );
+
+        let history = History(vec![i64::MIN, 0, 0]);
+        assert_eq!(history.differences(), Err(HistoryError::Overflow));
+
+        let data = parse_input("9223372036854775806 9223372036854775806\n2 2")
+            .expect("Example should parse successfully");
+        assert_eq!(
+            part_one(&data),
+            Err(ReportError {
+                line: 2,
+                error: HistoryError::Overflow
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_triangle() {
+        let input = read_to_string("src/input/day09-test.txt").expect("Could not read example");
+        let data = parse_input(&input).expect("Example should parse successfully");
+
+        let expected = "   0   3   6   9  12  15
+     3   3   3   3   3
+       0   0   0   0";
+        assert_eq!(data[0].render_triangle(), Ok(expected.to_string()));
+
+        // The differences are still shown when they don't reach zero.
+        let expected = "   1   2   4
+     1   2
+       1";
+        assert_eq!(
+            History(vec![1, 2, 4]).render_triangle(),
+            Ok(expected.to_string())
+        );
     }
 }
@@ -8,11 +8,48 @@
 //! There's a creature here with us, and
 //! we want to know more.
 
-use std::fs::read_to_string;
+use std::fs::{read_to_string, write};
 
 use simple_grid::{Grid, GridIndex};
+use thiserror::Error;
+
+use advent_2023::{Direction, ParseError};
+
+/// Every direction a pipe can go, in the order we check them.
+const DIRECTIONS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::East,
+    Direction::West,
+];
+
+/// Get the direction that points back the way we came.
+fn opposite(dir: Direction) -> Direction {
+    match dir {
+        Direction::North => Direction::South,
+        Direction::South => Direction::North,
+        Direction::East => Direction::West,
+        Direction::West => Direction::East,
+    }
+}
 
-use advent_2023::ParseError;
+#[derive(Clone, Debug, PartialEq, Error)]
+/// The ways that the maze can fail to make sense.
+enum MazeError {
+    #[error("Maze has no starting position")]
+    /// There's no 'S' anywhere in the maze.
+    MissingStart,
+    #[error("Maze has {0} starting positions")]
+    /// There's more than one 'S' in the maze.
+    MultipleStarts(usize),
+    #[error("Starting position is not part of a loop")]
+    /// No pipe at the starting position closes a loop.
+    NoLoop,
+    #[error("Starting position could close {0} different loops")]
+    /// More than one pipe at the starting position closes
+    /// a loop, so we can't tell which one is the real one.
+    AmbiguousStart(usize),
+}
 
 #[derive(Clone, Debug, PartialEq)]
 struct Maze(Grid<Pipe>);
@@ -20,210 +57,125 @@ struct Maze(Grid<Pipe>);
 impl Maze {
     /// Find the index of the creature's starting position.
     ///
-    /// # Panics
+    /// Returns an error unless there's exactly one.
+    fn find_start(&self) -> Result<GridIndex, MazeError> {
+        let starts: Vec<_> = self
+            .0
+            .indices()
+            .filter(|&idx| self.0[idx] == Pipe::StartingPosition)
+            .collect();
+
+        match starts[..] {
+            [] => Err(MazeError::MissingStart),
+            [idx] => Ok(idx),
+            _ => Err(MazeError::MultipleStarts(starts.len())),
+        }
+    }
+
+    /// Figure out which pipe is hiding under the starting position.
     ///
-    /// If the grid doesn't HAVE the starting position.
-    fn find_start(&self) -> GridIndex {
-        let Some(idx) = self.0.position(|pipe| *pipe == Pipe::StartingPosition) else {
-            unreachable!("Data should contain the creature's starting position");
-        };
+    /// A pipe could be there if both of its ends lead to pipes
+    /// that connect back to it. Usually only one pipe fits, but
+    /// if three or four neighbors connect to the start, we try
+    /// each pipe that fits and keep the one that closes a loop.
+    fn resolve_start(&self) -> Result<Pipe, MazeError> {
+        let start = self.find_start()?;
+
+        let closing: Vec<_> = Pipe::CONNECTED
+            .into_iter()
+            .filter(|pipe| {
+                let (one, two) = pipe.directions().expect("Pipe should connect somewhere");
+                [one, two].into_iter().all(|dir| {
+                    self.step(start, dir)
+                        .is_some_and(|idx| self.0[idx].connects(opposite(dir)) == Some(true))
+                })
+            })
+            .filter(|pipe| self.with_start(pipe.clone()).trace_loop(start).is_some())
+            .collect();
+
+        match closing[..] {
+            [] => Err(MazeError::NoLoop),
+            [ref pipe] => Ok(pipe.clone()),
+            _ => Err(MazeError::AmbiguousStart(closing.len())),
+        }
+    }
+
+    /// Get a copy of the maze with the starting position
+    /// replaced by a particular pipe.
+    fn with_start(&self, pipe: Pipe) -> Maze {
+        let mut grid = self.0.clone();
+        for idx in self.0.indices() {
+            if grid[idx] == Pipe::StartingPosition {
+                grid[idx] = pipe.clone();
+            }
+        }
+        Maze(grid)
+    }
 
-        idx
-    }
-
-    fn resolve_start(&self) -> Pipe {
-        let idx = self.find_start();
-        let north = idx
-            .up()
-            .is_some_and(|idx| self.0[idx].connects_south() == Some(true));
-        let south = idx
-            .down()
-            .is_some_and(|idx| self.0[idx].connects_north() == Some(true));
-        let east = idx
-            .right()
-            .is_some_and(|idx| self.0[idx].connects_west() == Some(true));
-        let west = idx
-            .left()
-            .is_some_and(|idx| self.0[idx].connects_east() == Some(true));
-
-        match (north, south, east, west) {
-            (true, true, _, _) => Pipe::NorthSouth,
-            (_, _, true, true) => Pipe::EastWest,
-            (true, _, true, _) => Pipe::NorthEast,
-            (true, _, _, true) => Pipe::NorthWest,
-            (_, true, true, _) => Pipe::SouthEast,
-            (_, true, _, true) => Pipe::SouthWest,
-            _ => unreachable!("Start pipe should have two neighbors"),
+    /// Get the index next to `idx` in a given direction,
+    /// or `None` if that would be off the edge of the maze.
+    fn step(&self, idx: GridIndex, dir: Direction) -> Option<GridIndex> {
+        match dir {
+            Direction::North => self.0.up_index(idx),
+            Direction::South => self.0.down_index(idx),
+            Direction::East => self.0.right_index(idx),
+            Direction::West => self.0.left_index(idx),
         }
     }
 
     fn pipe_neighbors(&self, idx: GridIndex) -> Option<(GridIndex, GridIndex)> {
         let pipe = &self.0[idx];
-        match pipe {
-            // We know for a fact there are no neighbors.
-            Pipe::Ground => None,
-            // These are a lot of repetitive code
-            // because we're repeating logic here.
-            Pipe::NorthSouth => {
+        match pipe.directions() {
+            Some((one, two)) => {
                 // Sanity checks to see that the two
                 // directions we're supposed to connect
                 // to actually exist.
-                let Some(north) = idx.up() else {
-                    return None;
-                };
-                let Some(south) = idx.down() else {
-                    return None;
-                };
-
-                // Get the neighboring pipes
-                let north_p = &self.0[north];
-                let south_p = &self.0[south];
+                let one_idx = self.step(idx, one)?;
+                let two_idx = self.step(idx, two)?;
 
                 // Check that the pipes connect back to us.
                 //
                 // Note that if we connect to StartingPosition,
                 // we may have None as our result, so we need
                 // to check that we did not receive an explicit `false`.
-                if north_p.connects_south() != Some(false)
-                    && south_p.connects_north() != Some(false)
-                {
-                    Some((north, south))
-                } else {
-                    None
-                }
-            }
-            Pipe::EastWest => {
-                let Some(east) = idx.right() else {
-                    return None;
-                };
-                let Some(west) = idx.left() else {
-                    return None;
-                };
-
-                let east_p = &self.0[east];
-                let west_p = &self.0[west];
-
-                if east_p.connects_west() != Some(false) && west_p.connects_east() != Some(false) {
-                    Some((east, west))
-                } else {
-                    None
-                }
-            }
-            Pipe::NorthEast => {
-                let Some(north) = idx.up() else {
-                    return None;
-                };
-                let Some(east) = idx.right() else {
-                    return None;
-                };
-
-                let north_p = &self.0[north];
-                let east_p = &self.0[east];
-
-                if north_p.connects_south() != Some(false) && east_p.connects_west() != Some(false)
-                {
-                    Some((north, east))
-                } else {
-                    None
-                }
-            }
-            Pipe::NorthWest => {
-                let Some(north) = idx.up() else {
-                    return None;
-                };
-                let Some(west) = idx.left() else {
-                    return None;
-                };
-
-                let north_p = &self.0[north];
-                let west_p = &self.0[west];
-
-                if north_p.connects_south() != Some(false) && west_p.connects_east() != Some(false)
-                {
-                    Some((north, west))
-                } else {
-                    None
-                }
-            }
-            Pipe::SouthEast => {
-                let Some(south) = idx.down() else {
-                    return None;
-                };
-                let Some(east) = idx.right() else {
-                    return None;
-                };
-
-                let south_p = &self.0[south];
-                let east_p = &self.0[east];
-
-                if south_p.connects_north() != Some(false) && east_p.connects_west() != Some(false)
-                {
-                    Some((south, east))
-                } else {
-                    None
-                }
-            }
-            Pipe::SouthWest => {
-                let Some(south) = idx.down() else {
-                    return None;
-                };
-                let Some(west) = idx.left() else {
-                    return None;
-                };
-
-                let south_p = &self.0[south];
-                let west_p = &self.0[west];
-
-                if south_p.connects_north() != Some(false) && west_p.connects_east() != Some(false)
+                if self.0[one_idx].connects(opposite(one)) != Some(false)
+                    && self.0[two_idx].connects(opposite(two)) != Some(false)
                 {
-                    Some((south, west))
+                    Some((one_idx, two_idx))
                 } else {
                     None
                 }
             }
+            // We know for a fact there are no neighbors.
+            None if *pipe == Pipe::Ground => None,
             // This is the complicated one.
-            Pipe::StartingPosition => {
-                let mut neighbors = Vec::new();
-
+            None => {
                 // We need to check each cardinal direction
                 // to see if it connects to this space.
                 //
                 // We can check for just `Some(true)` because
                 // this spot is the only one that could return `None`.
-                if let Some(north) = idx.up() {
-                    if self.0[north].connects_south() == Some(true) {
-                        neighbors.push(north);
-                    }
-                }
-                if let Some(south) = idx.down() {
-                    if self.0[south].connects_north() == Some(true) {
-                        neighbors.push(south);
-                    }
+                let neighbors: Vec<_> = DIRECTIONS
+                    .into_iter()
+                    .filter_map(|dir| {
+                        self.step(idx, dir)
+                            .filter(|&other| self.0[other].connects(opposite(dir)) == Some(true))
+                    })
+                    .collect();
+
+                // If more than two directions connect here,
+                // we can't tell which way to go.
+                match neighbors[..] {
+                    [one, two] => Some((one, two)),
+                    _ => None,
                 }
-                if let Some(east) = idx.right() {
-                    if self.0[east].connects_west() == Some(true) {
-                        neighbors.push(east);
-                    }
-                }
-                if let Some(west) = idx.left() {
-                    if self.0[west].connects_east() == Some(true) {
-                        neighbors.push(west);
-                    }
-                }
-
-                // Check that exactly two directions connect here.
-                assert_eq!(
-                    neighbors.len(),
-                    2,
-                    "starting position should connect to only two pipes"
-                );
-                Some((neighbors[0], neighbors[1]))
             }
         }
     }
 
-    fn find_loop(&self) -> Option<Vec<GridIndex>> {
-        let start = self.find_start();
+    /// Follow the pipes from `start` and see if they lead
+    /// back around to it, returning the pipes in the loop.
+    fn trace_loop(&self, start: GridIndex) -> Option<Vec<GridIndex>> {
         let (next, _) = self.pipe_neighbors(start)?;
 
         let mut curr = next;
@@ -231,6 +183,11 @@ impl Maze {
         loop {
             let (one, two) = self.pipe_neighbors(curr)?;
             let last = pipes.last()?;
+            // Make sure that we actually came from one of
+            // the two pipes this one connects to.
+            if one != *last && two != *last {
+                return None;
+            }
             let next = if one == *last { two } else { one };
             pipes.push(curr);
             curr = next;
@@ -241,6 +198,174 @@ impl Maze {
 
         Some(pipes)
     }
+
+    /// Get the maze with the starting position replaced
+    /// by its real pipe, along with where the start is.
+    fn resolved(&self) -> Result<(GridIndex, Maze), MazeError> {
+        let start = self.find_start()?;
+        let pipe = self.resolve_start()?;
+        Ok((start, self.with_start(pipe)))
+    }
+
+    /// Find the main loop, the one the creature is in.
+    fn find_loop(&self) -> Result<Vec<GridIndex>, MazeError> {
+        let (start, maze) = self.resolved()?;
+        maze.trace_loop(start).ok_or(MazeError::NoLoop)
+    }
+
+    /// Find every closed loop of pipes in the maze,
+    /// not just the one the creature is in.
+    ///
+    /// If the starting position can't be resolved,
+    /// it's left out of any loops.
+    fn closed_loops(&self) -> Vec<Vec<GridIndex>> {
+        let maze = match self.resolved() {
+            Ok((_, maze)) => maze,
+            Err(_) => self.with_start(Pipe::Ground),
+        };
+
+        // Each pipe only connects to two others, so each pipe is
+        // in at most one loop, and if following the pipes from
+        // somewhere doesn't loop, nothing we passed is in a loop.
+        let mut seen = Grid::new_default(maze.0.width(), maze.0.height());
+        let mut loops = vec![];
+        for idx in maze.0.indices() {
+            if seen[idx] || maze.0[idx] == Pipe::Ground {
+                continue;
+            }
+            seen[idx] = true;
+            match maze.trace_loop(idx) {
+                Some(pipes) => {
+                    for &pipe in pipes.iter() {
+                        seen[pipe] = true;
+                    }
+                    loops.push(pipes);
+                }
+                None => {
+                    // Mark everything we can reach from here as
+                    // a dead end so we don't trace it again.
+                    let mut queue = vec![idx];
+                    while let Some(curr) = queue.pop() {
+                        if let Some((one, two)) = maze.pipe_neighbors(curr) {
+                            for next in [one, two] {
+                                if !seen[next] {
+                                    seen[next] = true;
+                                    queue.push(next);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        loops
+    }
+
+    /// Get a copy of the maze with only the main loop in it.
+    ///
+    /// Every pipe that isn't part of the loop is
+    /// replaced with ground, and the starting position
+    /// is replaced with whatever pipe it really is.
+    fn clean(&self) -> Result<Grid<Pipe>, MazeError> {
+        let (start, maze) = self.resolved()?;
+        let main_loop = maze.trace_loop(start).ok_or(MazeError::NoLoop)?;
+        let mut grid = Grid::new_default(self.0.width(), self.0.height());
+        for pipe in main_loop.into_iter() {
+            grid[pipe] = maze.0[pipe].clone();
+        }
+        Ok(grid)
+    }
+
+    /// Count the tiles enclosed by the main loop.
+    ///
+    /// The loop is a polygon whose corners are the centers
+    /// of its pipes, so the shoelace formula gives us its
+    /// area. Pick's theorem then relates that area to the
+    /// number of tiles strictly inside it and the number
+    /// of tiles on the boundary, the latter being just the
+    /// length of the loop: `area = inside + boundary / 2 - 1`.
+    fn enclosed_area(&self) -> Result<usize, MazeError> {
+        let main_loop = self.find_loop()?;
+
+        // Twice the signed area, summed over every edge,
+        // wrapping around from the last pipe to the first.
+        let doubled: i64 = main_loop
+            .iter()
+            .zip(main_loop.iter().cycle().skip(1))
+            .map(|(one, two)| {
+                let (x1, y1) = (one.column() as i64, one.row() as i64);
+                let (x2, y2) = (two.column() as i64, two.row() as i64);
+                x1 * y2 - x2 * y1
+            })
+            .sum();
+        let doubled = doubled.unsigned_abs() as usize;
+
+        // Rearranging Pick's theorem, and keeping everything
+        // doubled until the end to avoid any fractions.
+        Ok((doubled + 2 - main_loop.len()) / 2)
+    }
+
+    /// Find which tiles are enclosed by the main loop.
+    ///
+    /// Going along each row, every time we cross a pipe
+    /// that goes north, we go from outside the loop to
+    /// inside it or vice versa. Corners that don't go
+    /// north only skim along the loop, so they don't count.
+    fn enclosed_cells(&self) -> Result<Grid<bool>, MazeError> {
+        let grid = self.clean()?;
+        let mut enclosed = Grid::new_default(grid.width(), grid.height());
+
+        for row in grid.rows() {
+            // Every row starts outside the loop.
+            let mut inside = false;
+            for column in grid.columns() {
+                let idx = GridIndex::new(column, row);
+                match grid[idx] {
+                    Pipe::Ground => enclosed[idx] = inside,
+                    Pipe::NorthSouth | Pipe::NorthEast | Pipe::NorthWest => inside = !inside,
+                    _ => {}
+                }
+            }
+        }
+        Ok(enclosed)
+    }
+
+    /// Count the tiles enclosed by the main loop the slow way,
+    /// by checking every tile with `enclosed_cells`.
+    fn enclosed_scanline(&self) -> Result<usize, MazeError> {
+        let enclosed = self.enclosed_cells()?;
+        Ok(enclosed.cell_iter().filter(|&&cell| cell).count())
+    }
+
+    /// Draw the maze so that the main loop is easy to see.
+    ///
+    /// The main loop is drawn with box-drawing characters
+    /// and the tiles it encloses are shaded in. If `color`
+    /// is set, everything else is drawn dimly using terminal
+    /// escape codes, otherwise it's left out entirely so the
+    /// result can be saved to a plain text file.
+    fn render(&self, color: bool) -> Result<String, MazeError> {
+        let grid = self.clean()?;
+        let enclosed = self.enclosed_cells()?;
+
+        let mut res = String::new();
+        for row in grid.rows() {
+            for column in grid.columns() {
+                let idx = GridIndex::new(column, row);
+                if grid[idx] != Pipe::Ground {
+                    res.push(grid[idx].box_char());
+                } else if enclosed[idx] {
+                    res.push('▒');
+                } else if color {
+                    res += &format!("\x1b[2m{}\x1b[0m", self.0[idx].symbol());
+                } else {
+                    res.push(' ');
+                }
+            }
+            res.push('\n');
+        }
+        Ok(res)
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -261,6 +386,67 @@ enum Pipe {
 }
 
 impl Pipe {
+    /// Every pipe that actually connects two directions.
+    const CONNECTED: [Pipe; 6] = [
+        Pipe::NorthSouth,
+        Pipe::EastWest,
+        Pipe::NorthEast,
+        Pipe::NorthWest,
+        Pipe::SouthEast,
+        Pipe::SouthWest,
+    ];
+
+    /// Get the two directions this pipe connects, if we know them.
+    fn directions(&self) -> Option<(Direction, Direction)> {
+        match *self {
+            Self::NorthSouth => Some((Direction::North, Direction::South)),
+            Self::EastWest => Some((Direction::East, Direction::West)),
+            Self::NorthEast => Some((Direction::North, Direction::East)),
+            Self::NorthWest => Some((Direction::North, Direction::West)),
+            Self::SouthEast => Some((Direction::South, Direction::East)),
+            Self::SouthWest => Some((Direction::South, Direction::West)),
+            Self::Ground | Self::StartingPosition => None,
+        }
+    }
+
+    /// Do we know if this pipe connects in a given direction?
+    fn connects(&self, dir: Direction) -> Option<bool> {
+        match dir {
+            Direction::North => self.connects_north(),
+            Direction::South => self.connects_south(),
+            Direction::East => self.connects_east(),
+            Direction::West => self.connects_west(),
+        }
+    }
+
+    /// Get the character this pipe is written as in the input.
+    fn symbol(&self) -> char {
+        match *self {
+            Self::Ground => '.',
+            Self::NorthSouth => '|',
+            Self::EastWest => '-',
+            Self::NorthEast => 'L',
+            Self::NorthWest => 'J',
+            Self::SouthEast => 'F',
+            Self::SouthWest => '7',
+            Self::StartingPosition => 'S',
+        }
+    }
+
+    /// Get a box-drawing character that looks like this pipe.
+    fn box_char(&self) -> char {
+        match *self {
+            Self::Ground => ' ',
+            Self::NorthSouth => '│',
+            Self::EastWest => '─',
+            Self::NorthEast => '└',
+            Self::NorthWest => '┘',
+            Self::SouthEast => '┌',
+            Self::SouthWest => '┐',
+            Self::StartingPosition => 'S',
+        }
+    }
+
     /// Do we know if this pipe connects to the north?
     fn connects_north(&self) -> Option<bool> {
         match *self {
@@ -343,45 +529,59 @@ fn parse_input(input: &str) -> Result<Maze, ParseError> {
     Ok(Maze(grid))
 }
 
-fn part_one(data: &Maze) -> Option<usize> {
+fn part_one(data: &Maze) -> Result<usize, MazeError> {
     let pipes = data.find_loop()?;
 
-    Some(pipes.len().div_ceil(2))
+    Ok(pipes.len().div_ceil(2))
 }
 
-#[allow(unused)]
-fn part_two(data: &Maze) -> Option<usize> {
-    let main_loop = data.find_loop()?;
-    let mut grid = Grid::new_default(data.0.width(), data.0.height());
-    for pipe in main_loop.into_iter() {
-        if data.0[pipe] == Pipe::StartingPosition {
-            grid[pipe] = data.resolve_start();
-        } else {
-            grid[pipe] = data.0[pipe].clone();
-        }
-    }
-
-    let mut inside = false;
-
-    Some(grid.into_iter().filter(|cell| match *cell {
-        Pipe::Ground => inside,
-        Pipe::NorthSouth | Pipe::NorthEast | Pipe::NorthWest => {
-            inside = !inside;
-            false
-        }
-        _ => false,
-    }).count())
+fn part_two(data: &Maze) -> Result<usize, MazeError> {
+    data.enclosed_area()
 }
 
 fn main() {
     let input = read_to_string("src/input/day10.txt").expect("Could not read input");
     let data = parse_input(&input).expect("Parsing should succeed");
 
+    // Draw the maze instead of solving it, if asked. This goes
+    // to the terminal unless a file is given with `--output`.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "render") {
+        match args.iter().position(|arg| arg == "--output") {
+            Some(idx) => {
+                let path = args.get(idx + 1).expect("Output file should be given");
+                let picture = data.render(false).expect("Loop should exist");
+                write(path, picture).expect("Could not write output");
+            }
+            None => print!("{}", data.render(true).expect("Loop should exist")),
+        }
+        return;
+    }
+
+    // List every loop in the maze, not just the creature's, if asked.
+    if args.first().is_some_and(|arg| arg == "loops") {
+        for pipes in data.closed_loops() {
+            let first = pipes[0];
+            println!(
+                "Loop through column {}, row {} has length {}",
+                first.column(),
+                first.row(),
+                pipes.len()
+            );
+        }
+        return;
+    }
+
     let one = part_one(&data).expect("Loop should exist");
     println!("Distance farthest from creature is {}", one);
 
     let two = part_two(&data).expect("Loop should exist");
     println!("Number of cells inside the loop is {}", two);
+
+    // Double check the answer by counting the other way.
+    if data.enclosed_scanline() != Ok(two) {
+        eprintln!("Warning: counting row by row gives a different answer");
+    }
 }
 
 #[cfg(test)]
@@ -416,7 +616,7 @@ mod test {
         let input = read_to_string("src/input/day10-test.txt").expect("Could not read input");
         let data = parse_input(&input).expect("Parsing should succeed");
 
-        assert_eq!(data.find_start(), GridIndex::new(0, 2));
+        assert_eq!(data.find_start(), Ok(GridIndex::new(0, 2)));
     }
 
     #[test]
@@ -445,7 +645,7 @@ mod test {
         let input = read_to_string("src/input/day10-test.txt").expect("Could not read input");
         let data = parse_input(&input).expect("Parsing should succeed");
 
-        assert!(data.find_loop().is_some_and(|pipes| pipes.len() == 16));
+        assert!(data.find_loop().is_ok_and(|pipes| pipes.len() == 16));
     }
 
     #[test]
@@ -453,6 +653,135 @@ mod test {
         let input = read_to_string("src/input/day10-test.txt").expect("Could not read input");
         let data = parse_input(&input).expect("Parsing should succeed");
 
-        assert_eq!(part_one(&data), Some(8));
+        assert_eq!(part_one(&data), Ok(8));
+    }
+
+    /// The examples for part two, along with how many
+    /// tiles each of them encloses.
+    const ENCLOSED_EXAMPLES: [(&str, usize); 3] = [
+        (
+            "...........
+.S-------7.
+.|F-----7|.
+.||.....||.
+.||.....||.
+.|L-7.F-J|.
+.|..|.|..|.
+.L--J.L--J.
+...........",
+            4,
+        ),
+        (
+            ".F----7F7F7F7F-7....
+.|F--7||||||||FJ....
+.||.FJ||||||||L7....
+FJL7L7LJLJ||LJ.L-7..
+L--J.L7...LJS7F-7L7.
+....F-J..F7FJ|L7L7L7
+....L7.F7||L7|.L7L7|
+.....|FJLJ|FJ|F7|.LJ
+....FJL-7.||.||||...
+....L---J.LJ.LJLJ...",
+            8,
+        ),
+        (
+            "FF7FSF7F7F7F7F7F---7
+L|LJ||||||||||||F--J
+FL-7LJLJ||||||LJL-77
+F--JF--7||LJLJ7F7FJ-
+L---JF-JLJ.||-FJLJJ7
+|F|F-JF---7F7-L7L|7|
+|FFJF7L7F-JF7|JL---7
+7-L-JL7||F7|L7F-7F7|
+L.L7LFJ|||||FJL7||LJ
+L7JLJL-JLJLJL--JLJ.L",
+            10,
+        ),
+    ];
+
+    #[test]
+    fn test_part_two() {
+        for (input, expected) in ENCLOSED_EXAMPLES {
+            let data = parse_input(input).expect("Parsing should succeed");
+
+            assert_eq!(part_two(&data), Ok(expected));
+        }
+
+        let input = read_to_string("src/input/day10-test.txt").expect("Could not read input");
+        let data = parse_input(&input).expect("Parsing should succeed");
+        assert_eq!(part_two(&data), Ok(1));
+    }
+
+    #[test]
+    fn test_enclosed_cross_check() {
+        for (input, _) in ENCLOSED_EXAMPLES {
+            let data = parse_input(input).expect("Parsing should succeed");
+
+            assert_eq!(data.enclosed_area(), data.enclosed_scanline());
+        }
+
+        let input = read_to_string("src/input/day10-test.txt").expect("Could not read input");
+        let data = parse_input(&input).expect("Parsing should succeed");
+        assert_eq!(data.enclosed_area(), data.enclosed_scanline());
+    }
+
+    #[test]
+    fn test_render() {
+        let input = read_to_string("src/input/day10-test.txt").expect("Could not read input");
+        let data = parse_input(&input).expect("Parsing should succeed");
+
+        let expected = ["  ┌┐ ", " ┌┘│ ", "┌┘▒└┐", "│┌──┘", "└┘   ", ""].join("\n");
+        assert_eq!(data.render(false), Ok(expected));
+
+        let colored = data.render(true).expect("Loop should exist");
+        assert!(colored.starts_with("\x1b[2m7\x1b[0m\x1b[2m-\x1b[0m┌┐"));
+    }
+
+    #[test]
+    fn test_start_errors() {
+        let data = parse_input("F-7\n|.|\nL-J").expect("Parsing should succeed");
+        assert_eq!(data.find_start(), Err(MazeError::MissingStart));
+
+        let data = parse_input("S-7\n|.|\nL-S").expect("Parsing should succeed");
+        assert_eq!(data.find_start(), Err(MazeError::MultipleStarts(2)));
+
+        let data = parse_input("S-7\n...\nL-J").expect("Parsing should succeed");
+        assert_eq!(data.resolve_start(), Err(MazeError::NoLoop));
+
+        // Both loops through the start close, so there's no telling
+        // which one the creature is in.
+        let data = parse_input("F-7.\n|.|.\nL-S7\n..||\n..LJ").expect("Parsing should succeed");
+        assert_eq!(data.resolve_start(), Err(MazeError::AmbiguousStart(2)));
+    }
+
+    #[test]
+    fn test_resolve_crowded_start() {
+        // Three pipes lead into the start, but only
+        // going north and west closes the loop.
+        let input = "\
+.....
+.F-7.
+.|.|.
+.L-S-
+...|.";
+        let data = parse_input(input).expect("Parsing should succeed");
+
+        assert_eq!(data.resolve_start(), Ok(Pipe::NorthWest));
+        assert_eq!(part_one(&data), Ok(4));
+        assert_eq!(part_two(&data), Ok(1));
+    }
+
+    #[test]
+    fn test_closed_loops() {
+        let input = "\
+F7.F-7
+LJ.|.|
+.S7L-J
+.LJ...";
+        let data = parse_input(input).expect("Parsing should succeed");
+
+        let mut lengths: Vec<_> = data.closed_loops().iter().map(Vec::len).collect();
+        lengths.sort();
+        assert_eq!(lengths, vec![4, 4, 8]);
     }
 }
@@ -8,6 +8,7 @@
 //! stars. Weird how the cosmic
 //! expansion seems to be bugged.
 
+use std::collections::BTreeMap;
 use std::fs::read_to_string;
 
 use simple_grid::{Grid, GridIndex};
@@ -30,53 +31,94 @@ struct Image {
 }
 
 impl Image {
-    /// Expand this space.
+    /// Expand this space, where every empty row and
+    /// column becomes `factor` empty rows or columns,
+    /// and return where the galaxies end up.
     ///
-    /// This uses the Part 1 assumption that
-    /// each empty row and column gets expanded
-    /// exactly once. This is as such entirely
-    /// useless for part 2.
-    fn expand(&self) -> Self {
-        let width = self.map.width();
+    /// Part 1 uses a factor of two and part 2 uses
+    /// a factor of a million, which is way too big
+    /// to actually build a grid out of, so we only
+    /// work out the new galaxy positions.
+    fn expanded_galaxies(&self, factor: usize) -> Vec<GridIndex> {
+        let rows = expansion_offsets(self.map.height(), &self.empty_rows(), factor);
+        let columns = expansion_offsets(self.map.width(), &self.empty_columns(), factor);
+
+        self.galaxies
+            .iter()
+            .map(|galaxy| {
+                GridIndex::new(
+                    galaxy.column() + columns[galaxy.column()],
+                    galaxy.row() + rows[galaxy.row()],
+                )
+            })
+            .collect()
+    }
 
-        let mut grid = self.map.clone();
+    /// Find the sum of the distances between every
+    /// pair of galaxies after expanding by `factor`.
+    fn distance_sum(&self, factor: usize) -> usize {
+        let galaxies = self.expanded_galaxies(factor);
+        // Manhattan distance is just the row distance plus
+        // the column distance, so we can add those up separately.
+        let rows = galaxies.iter().map(|galaxy| galaxy.row()).collect();
+        let columns = galaxies.iter().map(|galaxy| galaxy.column()).collect();
 
-        // Find the empty rows and store them in right-to-left order.
-        let empty_rows: Vec<usize> = grid
-            .rows()
-            .filter(|idx| grid.row_iter(*idx).all(|is_galaxy| !is_galaxy))
-            .rev()
-            .collect();
-        for row in empty_rows {
-            // Insert the rows. If we hadn't reversed
-            // `empty_rows`, this would add rows in the wrong places.
-            // Learned that the hard way.
-            grid.insert_row(row, vec![false; width]);
-        }
+        pairwise_sum(rows) + pairwise_sum(columns)
+    }
 
-        // This is the same algorithm but for columns.
-        
-        // Make sure we get the CURRENT height after adding rows.
-        let height = grid.height();
-        let empty_columns: Vec<usize> = grid
-            .columns()
-            .filter(|idx| grid.column_iter(*idx).all(|is_galaxy| !is_galaxy))
-            .rev()
-            .collect();
-        for column in empty_columns {
-            grid.insert_column(column, vec![false; height]);
-        }
+    /// Find the distance between two galaxies after expanding
+    /// by `factor`, or `None` if either galaxy doesn't exist.
+    ///
+    /// Galaxies are numbered from zero in reading order.
+    fn galaxy_distance(&self, one: usize, two: usize, factor: usize) -> Option<usize> {
+        let galaxies = self.expanded_galaxies(factor);
+        Some(index_distance(*galaxies.get(one)?, *galaxies.get(two)?))
+    }
+
+    /// Get the distance between every pair of galaxies after
+    /// expanding by `factor`, as the two galaxy numbers and
+    /// the distance between them.
+    fn pair_distances(&self, factor: usize) -> Vec<(usize, usize, usize)> {
+        let galaxies = self.expanded_galaxies(factor);
+        galaxies
+            .iter()
+            .enumerate()
+            .flat_map(|(one, &first)| {
+                galaxies
+                    .iter()
+                    .enumerate()
+                    .skip(one + 1)
+                    .map(move |(two, &second)| (one, two, index_distance(first, second)))
+            })
+            .collect()
+    }
 
-        // Get the new galaxy positions.
-        let galaxies = grid
-            .cells_with_indices_iter()
-            .filter_map(|(idx, is_galaxy)| if *is_galaxy { Some(idx) } else { None })
-            .collect();
+    /// Find the two galaxies closest to each other. If there's a
+    /// tie, the pair that comes first in reading order wins.
+    fn closest_pair(&self, factor: usize) -> Option<(usize, usize, usize)> {
+        self.pair_distances(factor)
+            .into_iter()
+            .min_by_key(|&(_, _, distance)| distance)
+    }
+
+    /// Find the two galaxies farthest from each other. If there's
+    /// a tie, the pair that comes first in reading order wins.
+    fn farthest_pair(&self, factor: usize) -> Option<(usize, usize, usize)> {
+        self.pair_distances(factor)
+            .into_iter()
+            .rev()
+            .max_by_key(|&(_, _, distance)| distance)
+    }
 
-        Self {
-            map: grid,
-            galaxies,
+    /// Count how many pairs of galaxies are each distance apart,
+    /// grouping the distances into buckets of `width`. Each bucket
+    /// is keyed by the smallest distance that goes in it.
+    fn distance_histogram(&self, factor: usize, width: usize) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for (_, _, distance) in self.pair_distances(factor) {
+            *histogram.entry(distance / width * width).or_insert(0) += 1;
         }
+        histogram
     }
 
     // Get the indices of rows that expand.
@@ -96,10 +138,47 @@ impl Image {
     }
 }
 
+/// Work out how far each of `len` rows (or columns) gets
+/// pushed along when every empty one grows to `factor` times
+/// its size. This is a running total of the empty ones so far.
+fn expansion_offsets(len: usize, empty: &[usize], factor: usize) -> Vec<usize> {
+    let mut offsets = Vec::with_capacity(len);
+    let mut empty = empty.iter().peekable();
+    let mut total = 0;
+    for idx in 0..len {
+        offsets.push(total);
+        if empty.next_if_eq(&&idx).is_some() {
+            total += factor - 1;
+        }
+    }
+    offsets
+}
+
+/// Find the sum of the differences between every pair of values.
+///
+/// Once the values are sorted, each one is bigger than everything
+/// before it, so it gets added once for each value before it and
+/// subtracted once for each value after it. That takes us from
+/// checking every pair to a single pass after sorting.
+fn pairwise_sum(mut values: Vec<usize>) -> usize {
+    values.sort_unstable();
+    let len = values.len();
+    values
+        .iter()
+        .enumerate()
+        .map(|(idx, &value)| value * idx)
+        .sum::<usize>()
+        - values
+            .iter()
+            .enumerate()
+            .map(|(idx, &value)| value * (len - 1 - idx))
+            .sum::<usize>()
+}
+
 #[inline]
 /// Find the [Manhattan distance][taxi] of two indices.
 ///
-/// [taxi]: https://en.wikipedia.org/wiki/Taxicab_geometry 
+/// [taxi]: https://en.wikipedia.org/wiki/Taxicab_geometry
 fn index_distance(one: GridIndex, two: GridIndex) -> usize {
     one.row().abs_diff(two.row()) + one.column().abs_diff(two.column())
 }
@@ -147,79 +226,106 @@ fn parse_input(input: &str) -> Result<Image, ParseError> {
 /// After the galaxy has expanded, find the distances
 /// from one galaxy to another and sum them up.
 fn part_one(data: &Image) -> usize {
-    let image = data.expand();
-    let last = image.galaxies.len() - 1;
-
-    let res = image.galaxies[0..=last]
-        .iter()
-        .enumerate()
-        .map(|(idx, galaxy)| {
-            (idx..image.galaxies.len())
-                .map(|idx| index_distance(*galaxy, image.galaxies[idx]))
-                .sum::<usize>()
-        })
-        .sum();
-
-    res
+    data.distance_sum(2)
 }
 
+/// Part 2
+/// ------
+///
+/// The galaxy is a LOT older than we thought, so
+/// every empty row and column is now a million
+/// times bigger. What's the sum of the distances now?
 fn part_two(data: &Image) -> usize {
-    let image = data;
-    let last = image.galaxies.len() - 1;
-
-    let res = image.galaxies[0..=last]
-        .iter()
-        .enumerate()
-        .map(|(idx, one)| {
-            (idx..image.galaxies.len())
-                .map(|idx| -> usize {
-                    let two = image.galaxies[idx];
-
-                    expanded_difference(*one, two, image)
-                })
-                .sum::<usize>()
-        })
-        .sum();
-
-    res
+    data.distance_sum(1_000_000)
 }
 
-/// The number of rows or columns to add when expanding for Part 2.
-const EXPANSION: usize = 1_000_000 - 1;
-
-/// Expand coordinates and return their distance.
-fn expanded_difference(one: GridIndex, two: GridIndex, image: &Image) -> usize {
-    let empty_rows = image.empty_rows();
-    let empty_columns = image.empty_columns();
-
-    // Check which rows we need to expand.
-    let row_diff_one = empty_rows.iter().filter(|&idx| *idx < one.row()).count();
-    let row_diff_two = empty_rows.iter().filter(|&idx| *idx < two.row()).count();
-    // Do an ad hoc expansion.
-    let row_one = one.row() + row_diff_one * EXPANSION;
-    let row_two = two.row() + row_diff_two * EXPANSION;
+/// Get the value following `--name` on the command line,
+/// or `default` if the option isn't there.
+fn option(args: &[String], name: &str, default: usize) -> usize {
+    match args.iter().position(|arg| arg == name) {
+        Some(idx) => args
+            .get(idx + 1)
+            .and_then(|value| value.parse::<usize>().ok())
+            .filter(|&value| value > 0)
+            .expect("Option should be a positive number"),
+        None => default,
+    }
+}
 
-    // Check which columns we need to expand.
-    let col_diff_one = empty_columns
-        .iter()
-        .filter(|&idx| *idx < one.column())
-        .count();
-    let col_diff_two = empty_columns
-        .iter()
-        .filter(|&idx| *idx < two.column())
-        .count();
-    // Again, perform the ad hoc expansion.
-    let col_one = one.column() + col_diff_one * EXPANSION;
-    let col_two = two.column() + col_diff_two * EXPANSION;
-
-    // Get the distance of the now expanded coordinates.
-    row_one.abs_diff(row_two) + col_one.abs_diff(col_two)
+/// Answer questions about the galaxies from the command line.
+///
+/// Every command takes `--factor` for how much to expand
+/// space by, which defaults to the factor from part 1.
+/// Galaxies are numbered from 1, the same as the puzzle.
+///
+/// - `expand`: the sum of the distances between every pair.
+/// - `distance <one> <two>`: the distance between two galaxies.
+/// - `extremes`: the closest and farthest pairs of galaxies.
+/// - `histogram`: how many pairs are each distance apart,
+///   grouped into buckets of size `--bucket`.
+fn run_command(data: &Image, command: &str, args: &[String]) {
+    let factor = option(args, "--factor", 2);
+    match command {
+        "expand" => println!(
+            "The sum distance of all galaxies expanded by {} is {}",
+            factor,
+            data.distance_sum(factor)
+        ),
+        "distance" => {
+            let galaxy = |idx: usize| {
+                args.get(idx)
+                    .and_then(|galaxy| galaxy.parse::<usize>().ok())
+                    .and_then(|galaxy| galaxy.checked_sub(1))
+                    .expect("Galaxy numbers should be given")
+            };
+            let (one, two) = (galaxy(0), galaxy(1));
+            let distance = data
+                .galaxy_distance(one, two, factor)
+                .expect("Galaxies should exist");
+            println!(
+                "Galaxies {} and {} are {} apart",
+                one + 1,
+                two + 1,
+                distance
+            );
+        }
+        "extremes" => {
+            let closest = data
+                .closest_pair(factor)
+                .expect("There should be two galaxies");
+            let farthest = data
+                .farthest_pair(factor)
+                .expect("There should be two galaxies");
+            for (label, (one, two, distance)) in [("closest", closest), ("farthest", farthest)] {
+                println!(
+                    "The {} galaxies are {} and {}, {} apart",
+                    label,
+                    one + 1,
+                    two + 1,
+                    distance
+                );
+            }
+        }
+        "histogram" => {
+            let width = option(args, "--bucket", 1);
+            for (start, count) in data.distance_histogram(factor, width) {
+                println!("{:>10}: {}", start, count);
+            }
+        }
+        _ => panic!("Unknown command {}", command),
+    }
 }
 
 fn main() {
     let input = read_to_string("src/input/day11.txt").expect("Could not read input");
     let data = parse_input(&input).expect("Parsing failed");
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        run_command(&data, command, &args[1..]);
+        return;
+    }
+
     println!(
         "The sum distance of all of the galaxies is {}",
         part_one(&data)
@@ -249,14 +355,16 @@ mod test {
         let input = read_to_string("src/input/day11-test.txt").expect("Could not read input");
         let data = parse_input(&input).expect("Parsing should succeed");
 
-        let image = data.expand();
+        let galaxies = data.expanded_galaxies(2);
         for row in [3, 4, 8, 9] {
             assert!(
-                image.map.row_iter(row).all(|is_galaxy| !is_galaxy),
+                galaxies.iter().all(|galaxy| galaxy.row() != row),
                 "Row {} is not empty",
                 row
             );
         }
+        assert_eq!(galaxies[0], GridIndex::new(4, 0));
+        assert_eq!(galaxies[8], GridIndex::new(5, 11));
     }
 
     #[test]
@@ -267,6 +375,49 @@ mod test {
         assert_eq!(part_one(&data), 374);
     }
 
+    #[test]
+    fn test_distance_sum() {
+        let input = read_to_string("src/input/day11-test.txt").expect("Could not read input");
+        let data = parse_input(&input).expect("Parsing should succeed");
+
+        assert_eq!(data.distance_sum(10), 1030);
+        assert_eq!(data.distance_sum(100), 8410);
+
+        // Check against adding up every pair the slow way.
+        let galaxies = data.expanded_galaxies(1_000);
+        let slow: usize = galaxies
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, &one)| {
+                galaxies[idx..]
+                    .iter()
+                    .map(move |&two| index_distance(one, two))
+            })
+            .sum();
+        assert_eq!(data.distance_sum(1_000), slow);
+    }
+
+    #[test]
+    fn test_galaxy_queries() {
+        let input = read_to_string("src/input/day11-test.txt").expect("Could not read input");
+        let data = parse_input(&input).expect("Parsing should succeed");
+
+        // These are the example pairs from the puzzle.
+        assert_eq!(data.galaxy_distance(4, 8, 2), Some(9));
+        assert_eq!(data.galaxy_distance(0, 6, 2), Some(15));
+        assert_eq!(data.galaxy_distance(2, 5, 2), Some(17));
+        assert_eq!(data.galaxy_distance(7, 8, 2), Some(5));
+        assert_eq!(data.galaxy_distance(0, 9, 2), None);
+
+        assert_eq!(data.pair_distances(2).len(), 36);
+        assert_eq!(data.closest_pair(2), Some((1, 3, 5)));
+        assert_eq!(data.farthest_pair(2), Some((1, 7, 19)));
+
+        let histogram = data.distance_histogram(2, 5);
+        assert_eq!(histogram.values().sum::<usize>(), 36);
+        assert_eq!(histogram.get(&15), Some(&7));
+    }
+
     // No test for part two because we aren't actually given that information.
 
     #[test]
@@ -0,0 +1,783 @@
+//! Day 12 of Advent of Code
+//!
+//! Hot Springs
+//! ===========
+//! The springs are broken and
+//! so are the records! We need
+//! to fix this fast before
+//! Gear Island panics! Or explodes!
+
+use std::fs::read_to_string;
+use std::iter::{from_fn, repeat_n, zip};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use advent_2023::ParseError;
+
+mod nonogram;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Record {
+    springs: Vec<SpringStatus>,
+    errors: Vec<usize>,
+}
+
+impl Record {
+    /// Unfold the record, repeating its springs `factor` times
+    /// with a `joiner` spring between each copy, and repeating
+    /// its groups of damaged springs `factor` times.
+    fn unfold(&self, factor: usize, joiner: SpringStatus) -> Record {
+        let springs = repeat_n(self.springs.clone(), factor)
+            .collect::<Vec<_>>()
+            .join(&joiner);
+        let errors = repeat_n(self.errors.clone(), factor)
+            .collect::<Vec<_>>()
+            .concat();
+
+        Record { springs, errors }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum SpringStatus {
+    Unknown,
+    Broken,
+    Okay,
+}
+
+impl From<SpringStatus> for char {
+    fn from(value: SpringStatus) -> Self {
+        match value {
+            SpringStatus::Okay => '.',
+            SpringStatus::Broken => '#',
+            SpringStatus::Unknown => '?',
+        }
+    }
+}
+
+impl TryFrom<char> for SpringStatus {
+    type Error = ParseError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '.' => Ok(Self::Okay),
+            '#' => Ok(Self::Broken),
+            '?' => Ok(Self::Unknown),
+            _ => Err(ParseError::InvalidFormat("valid spring character")),
+        }
+    }
+}
+
+/// Input consists of a series of records.
+///
+/// Each record consists of a line of characters,
+/// either `.`, `#`, or `?`, signifying an okay
+/// spring, a broken spring, or a spring of
+/// unknown status. This is then followed up by
+/// a list of the lengths of each row of broken
+/// springs for that record, determined by
+/// the rules of Paint by Numbers puzzles.
+fn parse_input(input: &str) -> Result<Vec<Record>, ParseError> {
+    input
+        .lines()
+        .map(|line| {
+            let Some((springs, errors)) = line.split_once(' ') else {
+                return Err(ParseError::InvalidFormat("proper record format"));
+            };
+
+            let springs = springs
+                .chars()
+                .map(SpringStatus::try_from)
+                .collect::<Result<Vec<_>, ParseError>>()?;
+
+            let errors = errors
+                .split(',')
+                .map(|err| err.parse().map_err(ParseError::ExpectedNumber))
+                .collect::<Result<Vec<usize>, ParseError>>()?;
+
+            Ok(Record { springs, errors })
+        })
+        .collect::<Result<Vec<Record>, ParseError>>()
+}
+
+/// A recursive function to solve the given record.
+fn solve(rec: &Record) -> u32 {
+    let springs = &rec.springs;
+    match springs
+        .iter()
+        .position(|spring| *spring == SpringStatus::Unknown)
+    {
+        Some(position) => {
+            // Solve for the unknown spring being okay and broken.
+            let mut yea = rec.clone();
+            yea.springs[position] = SpringStatus::Okay;
+
+            let mut nay = rec.clone();
+            nay.springs[position] = SpringStatus::Broken;
+
+            solve(&yea) + solve(&nay)
+        }
+        None => {
+            // println!("{:?}", springs);
+            let map = springs
+                // Get all of the runs of broken springs
+                .split(|spring| *spring == SpringStatus::Okay)
+                // Remove any empty lists
+                .filter(|list| !list.is_empty())
+                // Get the length of all of the lists
+                .map(|list| list.len())
+                // Collect into Vec for length check
+                .collect::<Vec<_>>();
+
+            // Check for equal length...
+            if map.len() == rec.errors.len()
+                // ...and equal elements.
+                && zip(map, &rec.errors).all(|(actual, &expected)| actual == expected)
+            {
+                1
+            } else {
+                0
+            }
+        }
+    }
+}
+
+/// Part 1
+/// ------
+///
+/// Given the information we have, how many possibilities
+/// are there that satisfy the constraints?
+fn part_one(data: &[Record]) -> u32 {
+    data.iter().map(solve).sum()
+}
+
+/// The dynamic programming table for a record.
+///
+/// This is indexed by (spring position, group index), where
+/// each entry holds the number of arrangements of `springs[i..]`
+/// that match `errors[g..]`. The table is filled from the end of
+/// the record backwards, so each entry only looks at entries that
+/// have already been computed, and the springs and errors are only
+/// ever borrowed.
+///
+/// Counts grow very quickly as records get longer, so an entry
+/// is `None` if its count doesn't fit in a `u128`. Entries that
+/// the whole record never uses are allowed to overflow.
+struct Table<'a> {
+    rec: &'a Record,
+    /// The number of consecutive springs starting at each
+    /// position that could all be broken.
+    runs: Vec<usize>,
+    ways: Vec<Option<u128>>,
+}
+
+impl<'a> Table<'a> {
+    fn new(rec: &'a Record) -> Self {
+        let len = rec.springs.len();
+        let width = rec.errors.len() + 1;
+
+        let mut runs = vec![0; len + 1];
+        for i in (0..len).rev() {
+            runs[i] = match rec.springs[i] {
+                SpringStatus::Okay => 0,
+                _ => runs[i + 1] + 1,
+            };
+        }
+
+        let mut table = Table {
+            rec,
+            runs,
+            ways: vec![Some(0); (len + 1) * width],
+        };
+        // Running off the end of the record is only fine
+        // if every group has already been placed.
+        table.ways[len * width + rec.errors.len()] = Some(1);
+
+        for i in (0..len).rev() {
+            for g in 0..width {
+                let mut total = Some(0u128);
+
+                if rec.springs[i] != SpringStatus::Broken {
+                    // Let's assume it's fine then.
+                    total = table.count(i + 1, g);
+                }
+
+                if let Some(next) = table.group_end(i, g) {
+                    total = total
+                        .zip(table.count(next, g + 1))
+                        .and_then(|(total, count)| total.checked_add(count));
+                }
+
+                table.ways[i * width + g] = total;
+            }
+        }
+
+        table
+    }
+
+    /// The number of arrangements of `springs[i..]` matching `errors[g..]`,
+    /// or `None` if there are too many to count.
+    fn count(&self, i: usize, g: usize) -> Option<u128> {
+        self.ways[i * (self.rec.errors.len() + 1) + g]
+    }
+
+    /// Whether `springs[i..]` can be arranged to match `errors[g..]` at all.
+    fn possible(&self, i: usize, g: usize) -> bool {
+        self.count(i, g) != Some(0)
+    }
+
+    /// Can group `g` start at spring `i`, with exactly enough
+    /// damaged springs followed by the end of the record or a
+    /// spring that could be okay? If so, this is the position
+    /// the rest of the record starts from.
+    fn group_end(&self, i: usize, g: usize) -> Option<usize> {
+        let springs = &self.rec.springs;
+        let group = *self.rec.errors.get(g)?;
+        let end = i + group;
+
+        if springs[i] == SpringStatus::Okay || self.runs[i] < group {
+            None
+        } else if end == springs.len() {
+            Some(end)
+        } else if springs[end] != SpringStatus::Broken {
+            Some(end + 1)
+        } else {
+            None
+        }
+    }
+
+    /// Place group `g` starting at spring `i` onto `prefix`,
+    /// given where the rest of the record starts.
+    fn place_group(&self, prefix: &mut Vec<SpringStatus>, g: usize, next: usize, i: usize) {
+        prefix.extend(repeat_n(SpringStatus::Broken, self.rec.errors[g]));
+        if next > i + self.rec.errors[g] {
+            prefix.push(SpringStatus::Okay);
+        }
+    }
+
+    /// Every concrete arrangement of the record, generated lazily.
+    ///
+    /// Only branches with at least one arrangement are explored,
+    /// so every step of the iterator makes progress. Arrangements
+    /// come out in the same order as they are numbered by `nth`.
+    fn arrangements(&self) -> impl Iterator<Item = Vec<SpringStatus>> + '_ {
+        let len = self.rec.springs.len();
+        let mut stack = Vec::new();
+        if self.possible(0, 0) {
+            stack.push((0, 0, Vec::with_capacity(len)));
+        }
+
+        from_fn(move || {
+            while let Some((i, g, prefix)) = stack.pop() {
+                if i == len {
+                    return Some(prefix);
+                }
+
+                // Pushed first so that it's explored second.
+                if let Some(next) = self.group_end(i, g) {
+                    if self.possible(next, g + 1) {
+                        let mut broken = prefix.clone();
+                        self.place_group(&mut broken, g, next, i);
+                        stack.push((next, g + 1, broken));
+                    }
+                }
+
+                if self.rec.springs[i] != SpringStatus::Broken && self.possible(i + 1, g) {
+                    let mut okay = prefix;
+                    okay.push(SpringStatus::Okay);
+                    stack.push((i + 1, g, okay));
+                }
+            }
+
+            None
+        })
+    }
+
+    /// The `n`th concrete arrangement of the record, counting from zero,
+    /// or `None` if there aren't that many arrangements.
+    ///
+    /// Counts too big to fit in a `u128` are always more than `n`.
+    fn nth(&self, mut n: u128) -> Option<Vec<SpringStatus>> {
+        if self.count(0, 0).is_some_and(|total| n >= total) {
+            return None;
+        }
+
+        let len = self.rec.springs.len();
+        let mut arrangement = Vec::with_capacity(len);
+        let (mut i, mut g) = (0, 0);
+        while i < len {
+            let okay = match self.rec.springs[i] {
+                SpringStatus::Broken => Some(0),
+                _ => self.count(i + 1, g),
+            };
+
+            match okay {
+                Some(okay) if n >= okay => {
+                    n -= okay;
+                    // The arrangement has to be in this branch, as
+                    // there are more than `n` arrangements left.
+                    let next = self.group_end(i, g)?;
+                    self.place_group(&mut arrangement, g, next, i);
+                    i = next;
+                    g += 1;
+                }
+                _ => {
+                    arrangement.push(SpringStatus::Okay);
+                    i += 1;
+                }
+            }
+        }
+
+        Some(arrangement)
+    }
+
+    /// A uniformly random concrete arrangement of the record.
+    ///
+    /// Returns `None` if there are no arrangements, or too
+    /// many to count, and so too many to pick between fairly.
+    fn sample(&self, rng: &mut Lcg) -> Option<Vec<SpringStatus>> {
+        match self.count(0, 0)? {
+            0 => None,
+            total => self.nth(rng.next(total)),
+        }
+    }
+}
+
+/// Solve the given record, in a way that doesn't take forever.
+///
+/// Returns `None` if there are too many arrangements to count.
+fn smart_solve(rec: &Record) -> Option<u128> {
+    Table::new(rec).count(0, 0)
+}
+
+/// A small linear congruential generator, so that random
+/// arrangements don't need to pull in a crate.
+struct Lcg(u64);
+
+impl Lcg {
+    /// Seed the generator from the system clock.
+    fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos());
+        Lcg(nanos as u64)
+    }
+
+    fn next_u32(&mut self) -> u32 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 32) as u32
+    }
+
+    /// A uniformly random number below `bound`.
+    fn next(&mut self, bound: u128) -> u128 {
+        // Reject values from the final, partial copy
+        // of the range, so every value is equally likely.
+        let zone = u128::MAX - u128::MAX % bound;
+        loop {
+            let value = (0..4).fold(0, |acc, _| (acc << 32) | self.next_u32() as u128);
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+/// Write out an arrangement the same way as the input.
+fn render(springs: &[SpringStatus]) -> String {
+    springs.iter().map(|&spring| char::from(spring)).collect()
+}
+
+/// Part 2
+/// ------
+///
+/// So it turns out the input we have is incomplete. By
+/// a factor of five. So, after duplicating the data we
+/// have to fit the proper sizes, we answer the same
+/// question: how many possibilities are there for the
+/// data?
+fn part_two(data: &[Record]) -> Option<u128> {
+    unfolded_total(data, 5, SpringStatus::Unknown)
+}
+
+/// The total number of arrangements of every record
+/// once it has been unfolded, or `None` if that's too many to count.
+fn unfolded_total(data: &[Record], factor: usize, joiner: SpringStatus) -> Option<u128> {
+    data.iter().try_fold(0u128, |total, rec| {
+        total.checked_add(smart_solve(&rec.unfold(factor, joiner))?)
+    })
+}
+
+/// Find the value given to an option on the command line.
+fn option<'a>(args: &'a [String], name: &str) -> Option<&'a str> {
+    args.iter().position(|arg| arg == name).map(|idx| {
+        args.get(idx + 1)
+            .expect("Option should have a value")
+            .as_str()
+    })
+}
+
+/// Parse a numeric option, falling back to `default` when it's missing.
+fn number<T: std::str::FromStr>(args: &[String], name: &str, default: T) -> T {
+    option(args, name).map_or(default, |value| {
+        value.parse().ok().expect("Option should be a number")
+    })
+}
+
+/// Inspect records from the command line.
+///
+/// - `unfold [--factor N] [--joiner C]`: the number of
+///   arrangements of each record and the total, where the
+///   records are repeated `N` times (default 5) joined by
+///   spring `C` (one of `.`, `#`, or `?`, default `?`).
+/// - `arrangements <record> [--limit N]`: list up to `N`
+///   (default 20) concrete arrangements of a record.
+/// - `sample <record> [--count N]`: draw `N` (default 1)
+///   uniformly random arrangements of a record.
+/// - `nonogram <file>`: solve the paint by numbers puzzle
+///   in `file` and draw the picture.
+///
+/// Records are numbered from 1, in the order of the input.
+fn run_command(data: &[Record], command: &str, args: &[String]) {
+    let record = || {
+        args.first()
+            .and_then(|idx| idx.parse::<usize>().ok())
+            .and_then(|idx| data.get(idx.checked_sub(1)?))
+            .expect("Expected a record number")
+    };
+
+    match command {
+        "unfold" => {
+            let factor = number(args, "--factor", 5);
+            let joiner = option(args, "--joiner").map_or(SpringStatus::Unknown, |joiner| {
+                let mut chars = joiner.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => {
+                        SpringStatus::try_from(c).expect("Joiner should be a spring")
+                    }
+                    _ => panic!("Joiner should be a single spring"),
+                }
+            });
+
+            let mut total = Some(0u128);
+            for (idx, rec) in data.iter().enumerate() {
+                let count = smart_solve(&rec.unfold(factor, joiner));
+                match count {
+                    Some(count) => println!("Record {}: {count}", idx + 1),
+                    None => println!("Record {}: too many arrangements to count", idx + 1),
+                }
+                total = total
+                    .zip(count)
+                    .and_then(|(total, count)| total.checked_add(count));
+            }
+            match total {
+                Some(total) => println!("Total arrangements unfolded {factor} times: {total}"),
+                None => println!("Total arrangements unfolded {factor} times is too many to count"),
+            }
+        }
+        "arrangements" => {
+            let rec = record();
+            let table = Table::new(rec);
+            let limit = number(args, "--limit", 20);
+
+            println!("{} {:?}", render(&rec.springs), rec.errors);
+            for arrangement in table.arrangements().take(limit) {
+                println!("{}", render(&arrangement));
+            }
+            match table.count(0, 0) {
+                Some(count) => println!("Showing up to {limit} of {count}"),
+                None => println!("Showing up to {limit} of too many to count"),
+            }
+        }
+        "sample" => {
+            let rec = record();
+            let table = Table::new(rec);
+            let mut rng = Lcg::from_time();
+
+            println!("{} {:?}", render(&rec.springs), rec.errors);
+            if table.count(0, 0).is_none() {
+                println!("There are too many arrangements to pick from fairly");
+                return;
+            }
+            for _ in 0..number(args, "--count", 1) {
+                match table.sample(&mut rng) {
+                    Some(arrangement) => println!("{}", render(&arrangement)),
+                    None => {
+                        println!("There are no valid arrangements");
+                        break;
+                    }
+                }
+            }
+        }
+        "nonogram" => {
+            let path = args.first().expect("Expected a puzzle file");
+            let input = read_to_string(path).expect("Could not load puzzle");
+            let puzzle = nonogram::parse_nonogram(&input).expect("Parsing failed");
+
+            match puzzle.solve() {
+                Some(picture) => print!("{}", nonogram::render_picture(&picture)),
+                None => println!("The puzzle has no solution"),
+            }
+        }
+        _ => panic!("Unknown command {command}"),
+    }
+}
+
+fn main() {
+    let input = read_to_string("src/input/day12.txt").expect("Could not load input");
+    let data = parse_input(&input).expect("Parsing failed");
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        run_command(&data, command, &args[1..]);
+        return;
+    }
+
+    println!(
+        "The total number of possible combinations is {}",
+        part_one(&data)
+    );
+    println!(
+        "The number of possibilities when unfolded is {}",
+        part_two(&data).expect("Too many possibilities to count")
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_input() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        let expected = Record {
+            springs: vec![
+                SpringStatus::Unknown,
+                SpringStatus::Unknown,
+                SpringStatus::Unknown,
+                SpringStatus::Okay,
+                SpringStatus::Broken,
+                SpringStatus::Broken,
+                SpringStatus::Broken,
+            ],
+            errors: vec![1, 1, 3],
+        };
+
+        assert_eq!(expected, data[0]);
+    }
+
+    #[test]
+    fn test_solve() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        assert_eq!(solve(&data[0]), 1);
+        assert_eq!(solve(&data[1]), 4);
+        assert_eq!(solve(&data[5]), 10);
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        assert_eq!(part_one(&data), 21);
+    }
+
+    #[test]
+    fn test_smart_solve() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        for rec in &data {
+            assert_eq!(smart_solve(rec), Some(solve(rec) as u128));
+        }
+    }
+
+    #[test]
+    fn test_smart_solve_random() {
+        let mut rng = Lcg(2023);
+
+        for _ in 0..1000 {
+            let len = 1 + rng.next(14) as usize;
+            let known: Vec<_> = (0..len)
+                .map(|_| match rng.next(2) {
+                    0 => SpringStatus::Okay,
+                    _ => SpringStatus::Broken,
+                })
+                .collect();
+
+            // Half of the time use the groups of a real arrangement,
+            // otherwise just make some up.
+            let errors = if rng.next(2) == 0 {
+                known
+                    .split(|spring| *spring == SpringStatus::Okay)
+                    .filter(|run| !run.is_empty())
+                    .map(|run| run.len())
+                    .collect()
+            } else {
+                (0..rng.next(5)).map(|_| 1 + rng.next(4) as usize).collect()
+            };
+
+            let springs = known
+                .into_iter()
+                .map(|spring| match rng.next(3) {
+                    0 => spring,
+                    _ => SpringStatus::Unknown,
+                })
+                .collect();
+
+            let rec = Record { springs, errors };
+            assert_eq!(smart_solve(&rec), Some(solve(&rec) as u128), "{rec:?}");
+        }
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        assert_eq!(part_two(&data), Some(525152));
+    }
+
+    #[test]
+    fn test_arrangements() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        let table = Table::new(&data[1]);
+        let arrangements: Vec<_> = table.arrangements().map(|arr| render(&arr)).collect();
+        assert_eq!(
+            arrangements,
+            [
+                "..#...#...###.",
+                "..#..#....###.",
+                ".#....#...###.",
+                ".#...#....###.",
+            ]
+        );
+
+        // Listing is lazy, so huge records are fine to peek at.
+        let unfolded = data[5].unfold(20, SpringStatus::Unknown);
+        assert_eq!(Table::new(&unfolded).arrangements().take(3).count(), 3);
+    }
+
+    #[test]
+    fn test_nth_and_sample() {
+        let mut rng = Lcg(12);
+
+        for _ in 0..200 {
+            let len = 1 + rng.next(12) as usize;
+            let springs: Vec<_> = (0..len)
+                .map(|_| match rng.next(3) {
+                    0 => SpringStatus::Okay,
+                    1 => SpringStatus::Broken,
+                    _ => SpringStatus::Unknown,
+                })
+                .collect();
+            let errors = (0..rng.next(4)).map(|_| 1 + rng.next(3) as usize).collect();
+            let rec = Record { springs, errors };
+            let table = Table::new(&rec);
+
+            let all: Vec<_> = table.arrangements().collect();
+            assert_eq!(Some(all.len() as u128), table.count(0, 0));
+            for (n, arrangement) in all.iter().enumerate() {
+                assert_eq!(table.nth(n as u128).as_ref(), Some(arrangement));
+
+                // Every arrangement is concrete, fits the record,
+                // and is counted by the brute force solver.
+                let concrete = Record {
+                    springs: arrangement.clone(),
+                    errors: rec.errors.clone(),
+                };
+                assert_eq!(solve(&concrete), 1);
+                assert!(zip(arrangement, &rec.springs)
+                    .all(|(&got, &had)| had == SpringStatus::Unknown || got == had));
+            }
+            assert_eq!(table.nth(all.len() as u128), None);
+
+            match table.sample(&mut rng) {
+                Some(arrangement) => assert!(all.contains(&arrangement)),
+                None => assert!(all.is_empty()),
+            }
+        }
+    }
+
+    #[test]
+    fn test_unfold() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        let unfolded = data[0].unfold(2, SpringStatus::Okay);
+        let expected = parse_input("???.###.???.### 1,1,3,1,1,3").expect("Parsing failed");
+        assert_eq!(unfolded, expected[0]);
+
+        // Unfolding once leaves the record alone.
+        assert_eq!(data[1].unfold(1, SpringStatus::Broken), data[1]);
+        assert_eq!(unfolded_total(&data, 1, SpringStatus::Unknown), Some(21));
+
+        // The last example has 10 arrangements, and each copy after
+        // the first has 15 thanks to the extra unknown spring.
+        let count = smart_solve(&data[5].unfold(20, SpringStatus::Unknown));
+        assert_eq!(count, Some(10 * 15u128.pow(19)));
+    }
+
+    #[test]
+    fn test_unfold_overflow() {
+        let input = read_to_string("src/input/day12-test.txt").expect("Could not load example");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        // 10 * 15^31 still fits in a u128, but 10 * 15^32 doesn't.
+        let count = smart_solve(&data[5].unfold(32, SpringStatus::Unknown));
+        assert_eq!(count, Some(10 * 15u128.pow(31)));
+
+        assert_eq!(
+            smart_solve(&data[5].unfold(33, SpringStatus::Unknown)),
+            None
+        );
+        assert_eq!(unfolded_total(&data, 35, SpringStatus::Unknown), None);
+        assert_eq!(
+            Table::new(&data[5].unfold(35, SpringStatus::Unknown)).count(0, 0),
+            None
+        );
+    }
+
+    #[test]
+    fn test_unreachable_overflow() {
+        // The first group has to go on the first spring, so the huge
+        // number of ways to arrange all of the groups after it never
+        // matters, only the C(125, 64) ways to arrange the rest.
+        let springs = [vec![SpringStatus::Broken], vec![SpringStatus::Unknown; 189]].concat();
+        let rec = Record {
+            springs,
+            errors: vec![1; 65],
+        };
+        let table = Table::new(&rec);
+
+        assert!((0..=65).any(|g| table.count(1, g).is_none()));
+        assert_eq!(
+            table.count(0, 0),
+            Some(2923171367321931373425996933337783875)
+        );
+        assert_eq!(smart_solve(&rec), table.count(0, 0));
+
+        let first = table.nth(0).expect("Record has arrangements");
+        assert_eq!(render(&first[..4]), "#...");
+        assert!(table.sample(&mut Lcg(7)).is_some());
+        assert_eq!(table.arrangements().take(2).count(), 2);
+
+        // Counts that don't fit still mean there are arrangements.
+        let springs = vec![SpringStatus::Unknown; 210];
+        let rec = Record {
+            springs,
+            errors: vec![1; 70],
+        };
+        let table = Table::new(&rec);
+        assert_eq!(table.count(0, 0), None);
+        assert!(table.nth(u128::MAX).is_some());
+        assert_eq!(table.sample(&mut Lcg(7)), None);
+    }
+}
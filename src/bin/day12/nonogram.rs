@@ -0,0 +1,244 @@
+//! Nonograms
+//! =========
+//!
+//! A record of springs is one row of a paint by numbers
+//! puzzle, so the same solver can fill in a whole picture.
+//! Every row and column is a record, and whatever one of
+//! them forces is fed into the records that cross it.
+
+use simple_grid::Grid;
+
+use advent_2023::{ParseError, ParseResult};
+
+use super::{render, smart_solve, Record, SpringStatus};
+
+/// The clues for every row and column of a puzzle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Nonogram {
+    rows: Vec<Vec<usize>>,
+    columns: Vec<Vec<usize>>,
+}
+
+/// Parse the clues for a single line.
+///
+/// A line with no filled cells is written as `0`.
+fn parse_clues(line: &str) -> ParseResult<Vec<usize>> {
+    if line.trim() == "0" {
+        return Ok(Vec::new());
+    }
+
+    line.split(',')
+        .map(|clue| match clue.trim().parse() {
+            Ok(0) => Err(ParseError::InvalidFormat("non-zero clue")),
+            Ok(clue) => Ok(clue),
+            Err(err) => Err(ParseError::ExpectedNumber(err)),
+        })
+        .collect()
+}
+
+/// Input consists of the row clues, a blank line,
+/// and then the column clues.
+///
+/// Each line holds the clues for one row (from the top)
+/// or column (from the left), in the same comma separated
+/// format as the spring records.
+pub fn parse_nonogram(input: &str) -> ParseResult<Nonogram> {
+    let Some((rows, columns)) = input.trim().split_once("\n\n") else {
+        return Err(ParseError::InvalidFormat("row and column clues"));
+    };
+
+    let rows = rows
+        .lines()
+        .map(parse_clues)
+        .collect::<ParseResult<Vec<_>>>()?;
+    let columns = columns
+        .lines()
+        .map(parse_clues)
+        .collect::<ParseResult<Vec<_>>>()?;
+
+    Ok(Nonogram { rows, columns })
+}
+
+/// Fill in every cell of `line` that all of its arrangements agree on.
+///
+/// Returns whether anything changed, or `None` if the
+/// line can't be arranged to match its clues at all.
+fn solve_line(line: &mut Vec<SpringStatus>, clues: &[usize]) -> Option<bool> {
+    let mut rec = Record {
+        springs: std::mem::take(line),
+        errors: clues.to_vec(),
+    };
+
+    if smart_solve(&rec) == Some(0) {
+        return None;
+    }
+
+    let mut changed = false;
+    for i in 0..rec.springs.len() {
+        if rec.springs[i] != SpringStatus::Unknown {
+            continue;
+        }
+
+        // Too many arrangements to count is still some arrangements.
+        rec.springs[i] = SpringStatus::Okay;
+        let okay = smart_solve(&rec) != Some(0);
+        rec.springs[i] = SpringStatus::Broken;
+        let broken = smart_solve(&rec) != Some(0);
+
+        rec.springs[i] = match (okay, broken) {
+            (true, false) => SpringStatus::Okay,
+            (false, true) => SpringStatus::Broken,
+            _ => SpringStatus::Unknown,
+        };
+        changed |= rec.springs[i] != SpringStatus::Unknown;
+    }
+
+    *line = rec.springs;
+    Some(changed)
+}
+
+impl Nonogram {
+    /// Fill in everything that follows from the clues one line
+    /// at a time, until nothing more can be worked out.
+    ///
+    /// Returns `None` if the picture contradicts the clues.
+    fn propagate(&self, mut grid: Grid<SpringStatus>) -> Option<Grid<SpringStatus>> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+
+            for (row, clues) in self.rows.iter().enumerate() {
+                let mut line = grid.row_iter(row).copied().collect();
+                if solve_line(&mut line, clues)? {
+                    grid.replace_row(row, line);
+                    changed = true;
+                }
+            }
+
+            for (column, clues) in self.columns.iter().enumerate() {
+                let mut line = grid.column_iter(column).copied().collect();
+                if solve_line(&mut line, clues)? {
+                    grid.replace_column(column, line);
+                    changed = true;
+                }
+            }
+        }
+
+        Some(grid)
+    }
+
+    /// Propagate what we know, then guess at the first
+    /// unknown cell and backtrack if the guess was wrong.
+    fn search(&self, grid: Grid<SpringStatus>) -> Option<Grid<SpringStatus>> {
+        let grid = self.propagate(grid)?;
+
+        let Some(idx) = grid.position(|&cell| cell == SpringStatus::Unknown) else {
+            return Some(grid);
+        };
+
+        [SpringStatus::Broken, SpringStatus::Okay]
+            .into_iter()
+            .find_map(|guess| {
+                let mut grid = grid.clone();
+                grid[idx] = guess;
+                self.search(grid)
+            })
+    }
+
+    /// Solve the puzzle, if it has a solution.
+    ///
+    /// If there are several, this is the first one found.
+    pub fn solve(&self) -> Option<Grid<SpringStatus>> {
+        let width = self.columns.len();
+        let height = self.rows.len();
+        let grid = Grid::new(width, height, vec![SpringStatus::Unknown; width * height]);
+
+        self.search(grid)
+    }
+}
+
+/// Draw the picture the same way as the spring records.
+pub fn render_picture(grid: &Grid<SpringStatus>) -> String {
+    grid.rows()
+        .map(|row| render(&grid.row_iter(row).copied().collect::<Vec<_>>()) + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::solve;
+
+    const HEART: &str = "1,1\n5\n5\n3\n1\n\n2\n4\n4\n4\n2\n";
+
+    #[test]
+    fn test_parse_nonogram() {
+        let puzzle = parse_nonogram("0\n2,1\n\n1\n0\n1\n1\n").expect("Parsing failed");
+
+        assert_eq!(puzzle.rows, [vec![], vec![2, 1]]);
+        assert_eq!(puzzle.columns, [vec![1], vec![], vec![1], vec![1]]);
+        assert!(parse_nonogram("1\n2\n").is_err());
+        assert!(parse_nonogram("1,0\n\n1\n").is_err());
+    }
+
+    #[test]
+    fn test_solve_line() {
+        let mut line = vec![SpringStatus::Unknown; 5];
+        assert_eq!(solve_line(&mut line, &[4]), Some(true));
+        assert_eq!(render(&line), "?###?");
+
+        assert_eq!(solve_line(&mut line, &[4]), Some(false));
+        assert_eq!(solve_line(&mut line, &[1, 1]), None);
+    }
+
+    #[test]
+    fn test_solve_line_long_contradiction() {
+        // The first group can't fit before the second broken spring,
+        // even though the rest of the line has more arrangements
+        // than fit in a u128.
+        let mut line = [
+            vec![SpringStatus::Broken; 2],
+            vec![SpringStatus::Unknown; 210],
+        ]
+        .concat();
+        assert_eq!(solve_line(&mut line, &[1; 71]), None);
+
+        // Lines with too many arrangements to count still get solved.
+        let mut line = [vec![SpringStatus::Broken], vec![SpringStatus::Unknown; 210]].concat();
+        assert_eq!(solve_line(&mut line, &[1; 70]), Some(true));
+        assert_eq!(render(&line[..2]), "#.");
+    }
+
+    #[test]
+    fn test_solve() {
+        let puzzle = parse_nonogram(HEART).expect("Parsing failed");
+        let picture = puzzle.solve().expect("Puzzle should have a solution");
+
+        assert_eq!(
+            render_picture(&picture),
+            ".#.#.\n#####\n#####\n.###.\n..#..\n"
+        );
+    }
+
+    #[test]
+    fn test_solve_ambiguous() {
+        // Both diagonals fit, so this needs a guess.
+        let puzzle = parse_nonogram("1\n1\n\n1\n1\n").expect("Parsing failed");
+        let picture = puzzle.solve().expect("Puzzle should have a solution");
+
+        for (row, clues) in puzzle.rows.iter().enumerate() {
+            let springs = picture.row_iter(row).copied().collect();
+            let errors = clues.clone();
+            assert_eq!(solve(&Record { springs, errors }), 1);
+        }
+        for (column, clues) in puzzle.columns.iter().enumerate() {
+            let springs = picture.column_iter(column).copied().collect();
+            let errors = clues.clone();
+            assert_eq!(solve(&Record { springs, errors }), 1);
+        }
+
+        let impossible = parse_nonogram("2\n0\n\n1\n0\n").expect("Parsing failed");
+        assert_eq!(impossible.solve(), None);
+    }
+}
@@ -7,99 +7,190 @@
 //! Unfortunately, mirrors are
 //! rather in the way.
 
-use std::collections::VecDeque as Deque;
 use std::fs::read_to_string;
 
-use simple_grid::Grid;
+use simple_grid::{Grid, GridIndex};
 
 use advent_2023::{ParseError, ParseResult};
 
 #[derive(Clone, Debug, PartialEq)]
 struct Frame(Grid<bool>);
 
-impl Frame {
-    /// Get the point where this frame mirrors.
-    fn mirror_row(&self) -> Option<usize> {
-        let deque = (self.0)
-            .rows()
-            .map(|row| self.0.row_iter(row).collect::<Vec<_>>())
-            .collect::<Deque<_>>();
-        (1..deque.len()).find(|&offset| {
-            let one = deque.iter().take(offset).rev();
-            let two = deque.iter().skip(offset);
-            // eprintln!(
-            //     "Left: {:?}\nRight: {:?}",
-            //     one.collect::<Vec<_>>(),
-            //     two.collect::<Vec<_>>()
-            // );
-            one.zip(two).all(|(row_one, row_two)| *row_one == *row_two)
-        })
+/// Which way a line of reflection runs through a frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Axis {
+    /// A horizontal line, between two rows.
+    Row,
+    /// A vertical line, between two columns.
+    Column,
+}
+
+/// A line of reflection, and how many cells
+/// have to change for the reflection to be perfect.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Reflection {
+    axis: Axis,
+    /// The number of rows above or columns left of the line.
+    position: usize,
+    differences: u32,
+}
+
+impl Reflection {
+    /// The value this reflection adds to the notes.
+    fn summary(&self) -> usize {
+        match self.axis {
+            Axis::Row => self.position * 100,
+            Axis::Column => self.position,
+        }
     }
+}
 
-    fn mirror_column(&self) -> Option<usize> {
-        let deque = (self.0)
-            .columns()
-            .map(|row| self.0.column_iter(row).collect::<Vec<_>>())
-            .collect::<Deque<_>>();
-        (1..deque.len()).find(|&offset| {
-            let one = deque.iter().take(offset).rev();
-            let two = deque.iter().skip(offset);
-            // eprintln!(
-            //     "Left: {:?}\nRight: {:?}",
-            //     one.collect::<Vec<_>>(),
-            //     two.collect::<Vec<_>>()
-            // );
-            one.zip(two).all(|(col_one, col_two)| *col_one == *col_two)
-        })
+impl Frame {
+    /// Every row or column of the frame as a bitmask,
+    /// with a set bit for each rock.
+    fn lines(&self, axis: Axis) -> Vec<u64> {
+        let encode = |cells: &mut dyn Iterator<Item = &bool>| {
+            cells.fold(0, |mask, &rock| (mask << 1) | u64::from(rock))
+        };
+
+        match axis {
+            Axis::Row => (self.0)
+                .rows()
+                .map(|row| encode(&mut self.0.row_iter(row)))
+                .collect(),
+            Axis::Column => (self.0)
+                .columns()
+                .map(|col| encode(&mut self.0.column_iter(col)))
+                .collect(),
+        }
     }
 
-    fn smudged_row(&self) -> Option<usize> {
-        let deque = (self.0)
-            .rows()
-            .map(|row| self.0.row_iter(row).collect::<Vec<_>>())
-            .collect::<Deque<_>>();
-        (1..deque.len()).find(|&offset| {
-            let one = deque.iter().take(offset).rev();
-            let two = deque.iter().skip(offset);
-            let iter = one.zip(two);
-
-            let diffs: usize = iter
-                .map(|(row_one, row_two)| {
-                    row_one
-                        .iter()
-                        .zip(row_two.iter())
-                        .filter(|(one, two)| one != two)
-                        .count()
+    /// Find every line of reflection along `axis` that
+    /// is at most `k` cells away from being perfect.
+    fn reflections(&self, axis: Axis, k: u32) -> Vec<Reflection> {
+        let lines = self.lines(axis);
+
+        (1..lines.len())
+            .filter_map(|position| {
+                let one = lines[..position].iter().rev();
+                let two = lines[position..].iter();
+
+                let differences = one
+                    .zip(two)
+                    .map(|(one, two)| (one ^ two).count_ones())
+                    .try_fold(0, |total, diff| {
+                        Some(total + diff).filter(|&total| total <= k)
+                    })?;
+
+                Some(Reflection {
+                    axis,
+                    position,
+                    differences,
                 })
-                .sum();
+            })
+            .collect()
+    }
 
-            diffs == 1
-        })
+    /// Find the first line of reflection along `axis`
+    /// that needs exactly `k` cells changed.
+    fn reflection(&self, axis: Axis, k: u32) -> Option<Reflection> {
+        self.reflections(axis, k)
+            .into_iter()
+            .find(|reflection| reflection.differences == k)
     }
 
-    fn smudged_column(&self) -> Option<usize> {
-        let deque = (self.0)
-            .columns()
-            .map(|col| self.0.column_iter(col).collect::<Vec<_>>())
-            .collect::<Deque<_>>();
-
-        (1..deque.len()).find(|&offset| {
-            let one = deque.iter().take(offset).rev();
-            let two = deque.iter().skip(offset);
-            let iter = one.zip(two);
-
-            let diffs: usize = iter
-                .map(|(col_one, col_two)| {
-                    col_one
-                        .iter()
-                        .zip(col_two.iter())
-                        .filter(|(one, two)| one != two)
-                        .count()
-                })
-                .sum();
+    /// Find the smudge, along with the line of reflection it spoils.
+    ///
+    /// The smudge is one of the two cells that differ across
+    /// a line of reflection that is one cell off perfect.
+    /// Fixing either of them works, so this is the one above
+    /// or to the left of the line.
+    fn smudge(&self) -> Option<(Reflection, GridIndex)> {
+        let reflection = [Axis::Column, Axis::Row]
+            .into_iter()
+            .find_map(|axis| self.reflection(axis, 1))?;
+
+        let lines = self.lines(reflection.axis);
+        let position = reflection.position;
+        let (line, diff) = (0..position.min(lines.len() - position))
+            .map(|offset| {
+                (
+                    position - 1 - offset,
+                    lines[position - 1 - offset] ^ lines[position + offset],
+                )
+            })
+            .find(|&(_, diff)| diff != 0)?;
+
+        // The first cell of each line is the highest bit of its mask.
+        let length = match reflection.axis {
+            Axis::Row => self.0.width(),
+            Axis::Column => self.0.height(),
+        };
+        let cell = length - 1 - diff.trailing_zeros() as usize;
+
+        let idx = match reflection.axis {
+            Axis::Row => GridIndex::new(cell, line),
+            Axis::Column => GridIndex::new(line, cell),
+        };
+        Some((reflection, idx))
+    }
 
-            diffs == 1
-        })
+    /// The frame as it should have been, with the smudge cleaned off.
+    fn repaired(&self) -> Option<Frame> {
+        let (_, idx) = self.smudge()?;
+        let mut frame = self.clone();
+        frame.0[idx] = !frame.0[idx];
+        Some(frame)
+    }
+
+    /// Draw the frame, with the line of reflection and the smudge marked.
+    ///
+    /// The line is drawn between the rows or columns it runs along.
+    /// If `color` is set, the smudge is highlighted using terminal
+    /// escape codes, otherwise it's drawn as an `X`.
+    fn render(
+        &self,
+        reflection: Option<Reflection>,
+        smudge: Option<GridIndex>,
+        color: bool,
+    ) -> String {
+        let line = |axis| {
+            reflection
+                .filter(|reflection| reflection.axis == axis)
+                .map(|reflection| reflection.position)
+        };
+        let (mirror_row, mirror_column) = (line(Axis::Row), line(Axis::Column));
+
+        let mut res = String::new();
+        for row in self.0.rows() {
+            if mirror_row == Some(row) {
+                for column in self.0.columns() {
+                    if mirror_column == Some(column) {
+                        res.push('┼');
+                    }
+                    res.push('─');
+                }
+                res.push('\n');
+            }
+
+            for column in self.0.columns() {
+                if mirror_column == Some(column) {
+                    res.push('│');
+                }
+
+                let idx = GridIndex::new(column, row);
+                let symbol = if self.0[idx] { '#' } else { '.' };
+                if smudge != Some(idx) {
+                    res.push(symbol);
+                } else if color {
+                    res += &format!("\x1b[7m{symbol}\x1b[0m");
+                } else {
+                    res.push('X');
+                }
+            }
+            res.push('\n');
+        }
+        res
     }
 }
 
@@ -120,13 +211,30 @@ fn parse_input(input: &str) -> ParseResult<Vec<Frame>> {
                         _ => Err(ParseError::InvalidFormat("valid pattern character")),
                     })
                     .collect::<ParseResult<Vec<bool>>>()?;
+                // Rows and columns are compared as bitmasks.
+                if vec.len() > 64 {
+                    return Err(ParseError::InvalidFormat("pattern at most 64 wide"));
+                }
                 map.push_row(vec);
             }
+            if map.height() > 64 {
+                return Err(ParseError::InvalidFormat("pattern at most 64 tall"));
+            }
             Ok(Frame(map))
         })
         .collect::<ParseResult<Vec<_>>>()
 }
 
+/// Sum up the first line of reflection along each
+/// axis of each frame, that needs exactly `k` cells changed.
+fn summarise(data: &[Frame], k: u32) -> usize {
+    data.iter()
+        .flat_map(|frame| [Axis::Column, Axis::Row].map(|axis| frame.reflection(axis, k)))
+        .flatten()
+        .map(|reflection| reflection.summary())
+        .sum()
+}
+
 /// Part 1
 /// ------
 ///
@@ -134,17 +242,7 @@ fn parse_input(input: &str) -> ParseResult<Vec<Frame>> {
 /// for each pattern, which could be
 /// horizontal or vertical.
 fn part_one(data: &[Frame]) -> usize {
-    let columns = data
-        .iter()
-        .filter_map(|frame| frame.mirror_column())
-        .sum::<usize>();
-    let rows = data
-        .iter()
-        .filter_map(|frame| frame.mirror_row())
-        .map(|row| row * 100)
-        .sum::<usize>();
-
-    columns + rows
+    summarise(data, 0)
 }
 
 /// Part 2
@@ -155,20 +253,56 @@ fn part_one(data: &[Frame]) -> usize {
 /// we're working with is incorrect, and we
 /// need to fix that before processing.
 fn part_two(data: &[Frame]) -> usize {
-    let columns: usize = data.iter().filter_map(|frame| frame.smudged_column()).sum();
-    let rows: usize = data
-        .iter()
-        .filter_map(|frame| frame.smudged_row())
-        .map(|row| row * 100)
-        .sum();
+    summarise(data, 1)
+}
 
-    columns + rows
+/// Show where the smudge on each frame is from the command line.
+///
+/// `smudge [<frame>] [--repaired]` prints every frame, or just
+/// the given one counting from 1, with its smudge highlighted
+/// and the line of reflection that appears once it's cleaned.
+/// With `--repaired`, the frame is drawn with the smudge fixed.
+fn print_smudges(data: &[Frame], args: &[String]) {
+    let repaired = args.iter().any(|arg| arg == "--repaired");
+    let only = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map(|arg| arg.parse::<usize>().expect("Frame should be a number"));
+
+    for (idx, frame) in data.iter().enumerate() {
+        if only.is_some_and(|only| only != idx + 1) {
+            continue;
+        }
+
+        match frame.smudge() {
+            Some((reflection, smudge)) => {
+                println!(
+                    "Frame {}: smudge at {smudge}, reflecting about {:?} {}",
+                    idx + 1,
+                    reflection.axis,
+                    reflection.position
+                );
+                let frame = match repaired {
+                    true => frame.repaired().expect("Frame has a smudge"),
+                    false => frame.clone(),
+                };
+                println!("{}", frame.render(Some(reflection), Some(smudge), true));
+            }
+            None => println!("Frame {}: no smudge found\n", idx + 1),
+        }
+    }
 }
 
 fn main() {
     let input = read_to_string("src/input/day13.txt").expect("Could not read file");
     let data = parse_input(&input).expect("Parsing should succeed");
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "smudge") {
+        print_smudges(&data, &args[1..]);
+        return;
+    }
+
     println!("The mirror sum is {}", part_one(&data));
     println!(
         "The mirror sum, now that we fixed our notes, is {}",
@@ -196,11 +330,13 @@ mod test {
         let input = read_to_string("src/input/day13-test.txt").expect("Could not read file");
         let data = parse_input(&input).expect("Parsing failed");
 
-        assert_eq!(data[0].mirror_column(), Some(5));
-        assert_eq!(data[0].mirror_row(), None);
+        let position = |frame: &Frame, axis| frame.reflection(axis, 0).map(|r| r.position);
+
+        assert_eq!(position(&data[0], Axis::Column), Some(5));
+        assert_eq!(position(&data[0], Axis::Row), None);
 
-        assert_eq!(data[1].mirror_row(), Some(4));
-        assert_eq!(data[1].mirror_column(), None);
+        assert_eq!(position(&data[1], Axis::Row), Some(4));
+        assert_eq!(position(&data[1], Axis::Column), None);
     }
 
     #[test]
@@ -216,8 +352,86 @@ mod test {
         let input = read_to_string("src/input/day13-test.txt").expect("Could not read file");
         let data = parse_input(&input).expect("Parsing failed");
 
-        assert_eq!(data[0].smudged_row(), Some(3));
-        assert_eq!(data[1].smudged_row(), Some(1));
+        let position = |frame: &Frame, axis| frame.reflection(axis, 1).map(|r| r.position);
+
+        assert_eq!(position(&data[0], Axis::Row), Some(3));
+        assert_eq!(position(&data[1], Axis::Row), Some(1));
+    }
+
+    #[test]
+    fn test_reflections() {
+        let input = read_to_string("src/input/day13-test.txt").expect("Could not read file");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        let reflection = |position, differences| Reflection {
+            axis: Axis::Row,
+            position,
+            differences,
+        };
+
+        assert_eq!(
+            data[1].reflections(Axis::Row, 1),
+            [reflection(1, 1), reflection(4, 0)]
+        );
+        assert_eq!(data[0].reflections(Axis::Row, 0), []);
+        assert_eq!(data[0].reflections(Axis::Row, 1), [reflection(3, 1)]);
+
+        // With enough leeway, every line is a reflection.
+        let all = data[0].reflections(Axis::Column, 64);
+        assert_eq!(all.len(), 8);
+        assert_eq!(all.iter().filter(|r| r.differences == 0).count(), 1);
+    }
+
+    #[test]
+    fn test_smudge() {
+        let input = read_to_string("src/input/day13-test.txt").expect("Could not read file");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        let (reflection, smudge) = data[0].smudge().expect("Frame has a smudge");
+        assert_eq!((reflection.axis, reflection.position), (Axis::Row, 3));
+        assert_eq!(smudge, GridIndex::new(0, 0));
+
+        let (reflection, smudge) = data[1].smudge().expect("Frame has a smudge");
+        assert_eq!((reflection.axis, reflection.position), (Axis::Row, 1));
+        assert_eq!(smudge, GridIndex::new(4, 0));
+
+        // Cleaning the smudge leaves a perfect reflection.
+        for frame in &data {
+            let repaired = frame.repaired().expect("Frame has a smudge");
+            let (reflection, _) = frame.smudge().expect("Frame has a smudge");
+            let perfect = repaired.reflection(reflection.axis, 0);
+            assert!(perfect.is_some_and(|perfect| perfect.position == reflection.position));
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let input = read_to_string("src/input/day13-test.txt").expect("Could not read file");
+        let data = parse_input(&input).expect("Parsing failed");
+
+        let (reflection, smudge) = data[1].smudge().expect("Frame has a smudge");
+        let expected = "\
+#...X#..#
+─────────
+#....#..#
+..##..###
+#####.##.
+#####.##.
+..##..###
+#....#..#
+";
+        assert_eq!(
+            data[1].render(Some(reflection), Some(smudge), false),
+            expected
+        );
+
+        let column = data[0].reflection(Axis::Column, 0);
+        let expected = "#.##.│.##.\n..#.#│#.#.\n";
+        assert!(data[0].render(column, None, false).starts_with(expected));
+
+        let (reflection, smudge) = data[1].smudge().expect("Frame has a smudge");
+        let colored = data[1].render(Some(reflection), Some(smudge), true);
+        assert!(colored.contains("\x1b[7m#\x1b[0m"));
     }
 
     #[test]
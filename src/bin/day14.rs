@@ -11,9 +11,10 @@
 
 use std::fs::read_to_string;
 
-use simple_grid::Grid;
+use simple_grid::{Grid, GridIndex};
 
-use advent_2023::{ParseError, ParseResult};
+use advent_2023::cycle::{self, Cycle};
+use advent_2023::{Direction, ParseError, ParseResult};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 enum Rock {
@@ -48,28 +49,59 @@ fn parse_input(input: &str) -> ParseResult<Grid<Rock>> {
     Ok(grid)
 }
 
-fn tilt_north(grid: &Grid<Rock>) -> Grid<Rock> {
-    let mut out = Grid::new(0, 0, vec![]);
-
-    for col in grid.columns() {
-        let column = grid.column_iter(col).map(|&rock| rock).collect::<Vec<_>>();
-
-        let chunks = column
-            .split(|&rock| rock == Rock::Cube)
-            .map(|chunk| {
-                let (rounds, grounds): (Vec<Rock>, Vec<Rock>) =
-                    chunk.iter().partition(|&rock| *rock == Rock::Round);
-                [rounds, grounds].concat()
-            })
-            .collect::<Vec<Vec<_>>>();
-
-        let column = chunks.join(&Rock::Cube);
-
-        out.push_column(column);
+/// Tilt the dish so that every round rock rolls as far
+/// as it can towards `direction`, without copying the grid.
+fn tilt(grid: &mut Grid<Rock>, direction: Direction) {
+    let (width, height) = grid.dimensions();
+
+    // Each line runs along the tilt, starting from the edge the
+    // rocks roll towards, so `step` counts away from that edge.
+    let (lines, length) = match direction {
+        Direction::North | Direction::South => (width, height),
+        Direction::East | Direction::West => (height, width),
+    };
+    let index = |line: usize, step: usize| match direction {
+        Direction::North => GridIndex::new(line, step),
+        Direction::South => GridIndex::new(line, height - 1 - step),
+        Direction::West => GridIndex::new(step, line),
+        Direction::East => GridIndex::new(width - 1 - step, line),
+    };
+
+    for line in 0..lines {
+        // The next place a rock rolling along this line would stop.
+        let mut free = 0;
+        for step in 0..length {
+            match grid[index(line, step)] {
+                Rock::Cube => free = step + 1,
+                Rock::Round => {
+                    if free != step {
+                        grid[index(line, free)] = Rock::Round;
+                        grid[index(line, step)] = Rock::Ground;
+                    }
+                    free += 1;
+                }
+                Rock::Ground => {}
+            }
+        }
     }
+}
 
-    assert_eq!(grid.dimensions(), out.dimensions());
-    out
+/// The load the round rocks put on the supports along `edge`.
+///
+/// Each rock adds the number of rows or columns between it
+/// and the opposite edge, counting its own.
+fn load(grid: &Grid<Rock>, edge: Direction) -> usize {
+    let (width, height) = grid.dimensions();
+
+    grid.cells_with_indices_iter()
+        .filter(|(_, &rock)| rock == Rock::Round)
+        .map(|(idx, _)| match edge {
+            Direction::North => height - idx.row(),
+            Direction::South => idx.row() + 1,
+            Direction::West => width - idx.column(),
+            Direction::East => idx.column() + 1,
+        })
+        .sum()
 }
 
 /// Part 1
@@ -79,89 +111,93 @@ fn tilt_north(grid: &Grid<Rock>) -> Grid<Rock> {
 /// rocks move upward. How much stress
 /// does this action place upon the dish?
 fn part_one(data: &Grid<Rock>) -> usize {
-    let grid = tilt_north(data);
-
-    grid.rows()
-        .map(|row| {
-            let count = grid
-                .row_iter(row)
-                .filter(|&rock| *rock == Rock::Round)
-                .count();
-            count * (grid.height() - row)
-        })
-        .sum()
-}
-
-const REPETITIONS: u32 = 1_000_000_000;
-
-/// Detect cycles for a function
-fn detect_cycle<T, F>(f: F, initial: T) -> (u32, u32)
-where
-    F: Fn(T) -> T,
-    T: PartialEq<T> + Clone,
-{
-    let mut power = 1;
-    let mut cycle_length = 1;
-    let mut slow = initial.clone();
-    let mut fast = f(initial.clone());
-
-    while fast != slow {
-        if cycle_length == power {
-            slow = fast.clone();
-            power *= 2;
-            cycle_length = 0;
-        }
-        fast = f(fast);
-        cycle_length += 1;
-    }
+    let mut grid = data.clone();
+    tilt(&mut grid, Direction::North);
 
-    slow = initial.clone();
-    fast = initial.clone();
+    load(&grid, Direction::North)
+}
 
-    for _ in 0..cycle_length {
-        fast = f(fast);
+const REPETITIONS: u64 = 1_000_000_000;
+
+/// Spin the dish once, tilting it north, west, south and then east.
+fn spin_cycle(grid: &mut Grid<Rock>) {
+    for direction in [
+        Direction::North,
+        Direction::West,
+        Direction::South,
+        Direction::East,
+    ] {
+        tilt(grid, direction);
     }
+}
 
-    let mut cycle_start = 0;
-    while fast != slow {
-        slow = f(slow);
-        fast = f(fast);
-        cycle_start += 1;
-    }
+/// Find where spinning the dish over and over starts repeating.
+fn spin_cycles(data: &Grid<Rock>) -> Cycle<Grid<Rock>> {
+    let spin = |mut grid| {
+        spin_cycle(&mut grid);
+        grid
+    };
 
-    (cycle_length, cycle_start)
+    cycle::brent(spin, data.clone())
 }
 
-fn tilt_cycle(grid: Grid<Rock>) -> Grid<Rock> {
-    let mut out = grid.clone();
-    for _ in 0..4 {
-        out = tilt_north(&out);
-        out.rotate_cw();
-    }
-    out
+/// The load on the north supports after `cycles` spin cycles.
+fn load_after(cycles: &Cycle<Grid<Rock>>, count: u64) -> usize {
+    load(cycles.state_at(count), Direction::North)
 }
 
 /// Part 2
 fn part_two(data: &Grid<Rock>) -> usize {
-    let (length, start) = detect_cycle(tilt_cycle, data.clone());
-    let length = start + (REPETITIONS - start) % length;
-    let grid = (0..length).fold(data.clone(), |acc, _| tilt_cycle(acc));
-
-    grid.rows()
-        .map(|row| {
-            let count = grid
-                .row_iter(row)
-                .filter(|&rock| *rock == Rock::Round)
-                .count();
-            count * (grid.height() - row)
-        })
-        .sum()
+    load_after(&spin_cycles(data), REPETITIONS)
+}
+
+/// Spin the dish from the command line.
+///
+/// `cycles [<count>] [--loads]` prints where the spin cycles
+/// start repeating and the load after `count` spin cycles,
+/// which defaults to the billion from part 2. With `--loads`,
+/// it also prints the load after every spin cycle up to the
+/// end of the first time around the loop.
+fn print_cycles(data: &Grid<Rock>, args: &[String]) {
+    let count = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .map_or(REPETITIONS, |count| {
+            count.parse().expect("Count should be a number")
+        });
+    let cycles = spin_cycles(data);
+
+    println!(
+        "The spin cycles start repeating after {} and repeat every {}",
+        cycles.start, cycles.length
+    );
+
+    if args.iter().any(|arg| arg == "--loads") {
+        for (step, grid) in cycles.states().iter().enumerate() {
+            let marker = match step as u64 == cycles.start {
+                true => " <- loop starts",
+                false => "",
+            };
+            println!("{step}: {}{marker}", load(grid, Direction::North));
+        }
+    }
+
+    println!(
+        "The total load after {count} cycles is {}",
+        load_after(&cycles, count)
+    );
 }
 
 fn main() {
     let input = read_to_string("src/input/day14.txt").expect("Could not read input");
     let data = parse_input(&input).expect("Parsing failed");
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "cycles") {
+        print_cycles(&data, &args[1..]);
+        return;
+    }
+
     println!("The total load on the supports is {}", part_one(&data));
     println!(
         "The total load after a billion cycles is {}",
@@ -188,9 +224,10 @@ mod test {
         let input = read_to_string("src/input/day14-test.txt").expect("Could not read example");
         let data = parse_input(&input).expect("Parsing failed");
 
-        let grid = tilt_north(&data);
+        let mut grid = data.clone();
+        tilt(&mut grid, Direction::North);
 
-        let actual = grid.column_iter(0).map(|&rock| rock).collect::<Vec<_>>();
+        let actual = grid.column_iter(0).copied().collect::<Vec<_>>();
         let expected: Vec<_> = [
             vec![Rock::Round; 4],
             vec![Rock::Ground; 4],
@@ -202,20 +239,55 @@ mod test {
     }
 
     #[test]
-    fn test_part_one() {
+    fn test_tilt_directions() {
         let input = read_to_string("src/input/day14-test.txt").expect("Could not read example");
         let data = parse_input(&input).expect("Parsing failed");
 
-        assert_eq!(part_one(&data), 136);
+        // Tilting any way is the same as turning the dish so that
+        // way is north, tilting north, then turning it back.
+        let turns = [
+            (Direction::North, 0),
+            (Direction::West, 1),
+            (Direction::South, 2),
+            (Direction::East, 3),
+        ];
+        for (direction, turns) in turns {
+            let mut expected = data.clone();
+            for _ in 0..turns {
+                expected.rotate_cw();
+            }
+            tilt(&mut expected, Direction::North);
+            for _ in 0..turns {
+                expected.rotate_ccw();
+            }
+
+            let mut grid = data.clone();
+            tilt(&mut grid, direction);
+            assert_eq!(grid, expected, "tilting {direction:?}");
+        }
     }
 
     #[test]
-    fn test_detect_cycle() {
-        fn test_func(val: u32) -> u32 {
-            (val * val + 1) % 255
-        }
+    fn test_load() {
+        let grid = parse_input("O.#\n..O\n.O.\n").expect("Parsing failed");
+
+        assert_eq!(load(&grid, Direction::North), 3 + 2 + 1);
+        assert_eq!(load(&grid, Direction::South), 1 + 2 + 3);
+        assert_eq!(load(&grid, Direction::West), 3 + 1 + 2);
+        assert_eq!(load(&grid, Direction::East), 1 + 3 + 2);
+
+        let input = read_to_string("src/input/day14-test.txt").expect("Could not read example");
+        let mut data = parse_input(&input).expect("Parsing failed");
+        spin_cycle(&mut data);
+        assert_eq!(load(&data, Direction::North), 87);
+    }
+
+    #[test]
+    fn test_part_one() {
+        let input = read_to_string("src/input/day14-test.txt").expect("Could not read example");
+        let data = parse_input(&input).expect("Parsing failed");
 
-        assert_eq!(detect_cycle(test_func, 3), (6, 2));
+        assert_eq!(part_one(&data), 136);
     }
 
     #[test]
@@ -223,9 +295,9 @@ mod test {
         let input = read_to_string("src/input/day14-test.txt").expect("Could not read example");
         let data = parse_input(&input).expect("Parsing failed");
 
-        let grid = tilt_cycle(data);
+        let mut grid = data;
+        spin_cycle(&mut grid);
 
-        println!("{:?}", grid.row_iter(0).collect::<Vec<_>>());
         assert_eq!(grid.row_iter(0).position(|&rock| rock == Rock::Round), None);
         assert_eq!(
             grid.row_iter(1).position(|&rock| rock == Rock::Round),
@@ -240,4 +312,24 @@ mod test {
 
         assert_eq!(part_two(&data), 64);
     }
+
+    #[test]
+    fn test_load_after() {
+        let input = read_to_string("src/input/day14-test.txt").expect("Could not read example");
+        let data = parse_input(&input).expect("Parsing failed");
+        let cycles = spin_cycles(&data);
+
+        assert_eq!(cycles.mu_lambda(), (3, 7));
+
+        let mut grid = data.clone();
+        for count in 0..30 {
+            assert_eq!(load_after(&cycles, count), load(&grid, Direction::North));
+            spin_cycle(&mut grid);
+        }
+
+        assert_eq!(
+            load_after(&cycles, u64::MAX),
+            load_after(&cycles, 3 + (u64::MAX - 3) % 7)
+        );
+    }
 }
@@ -7,7 +7,6 @@
 //! it's just a simple hashing
 //! algorithm to deal with...
 
-use std::collections::HashMap;
 use std::fs::read_to_string;
 
 use advent_2023::{ParseError, ParseResult};
@@ -16,7 +15,7 @@ use advent_2023::{ParseError, ParseResult};
 /// list of strings to hash together.
 ///
 /// For once, this function is infalliable!
-fn parse_input<'i>(input: &'i str) -> Vec<&'i str> {
+fn parse_input(input: &str) -> Vec<&str> {
     input.trim().split(',').collect()
 }
 
@@ -53,7 +52,7 @@ impl<'i> TryFrom<&'i str> for Instruction<'i> {
     type Error = ParseError;
 
     fn try_from(value: &'i str) -> Result<Self, Self::Error> {
-        match value.find(&['-', '=']) {
+        match value.find(['-', '=']) {
             None => Err(ParseError::InvalidFormat("valid operation character")),
             Some(position) => {
                 let (label, operation) = value.split_at(position);
@@ -90,6 +89,127 @@ enum Operation {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 struct Lens<'a>(&'a str, usize);
 
+/// The 256 boxes lined up along the light's path.
+///
+/// Each box holds its lenses in the order they were put in,
+/// and a label picks its box by its HASH.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LensBoxes<'a> {
+    boxes: [Vec<Lens<'a>>; BOXES],
+}
+
+const BOXES: usize = REDUCTION as usize;
+
+impl<'a> LensBoxes<'a> {
+    fn new() -> Self {
+        LensBoxes {
+            boxes: std::array::from_fn(|_| Vec::new()),
+        }
+    }
+
+    /// Find which box a label goes in, and where it is inside it.
+    fn find(&self, label: &str) -> (usize, Option<usize>) {
+        let slot = hashvent(label) as usize;
+        let position = self.boxes[slot].iter().position(|lens| lens.0 == label);
+        (slot, position)
+    }
+
+    /// Put a lens in its box, replacing the lens
+    /// with the same label if there is one already.
+    fn insert(&mut self, label: &'a str, focus: usize) {
+        match self.find(label) {
+            (slot, Some(position)) => self.boxes[slot][position].1 = focus,
+            (slot, None) => self.boxes[slot].push(Lens(label, focus)),
+        }
+    }
+
+    /// Take the lens with this label out of its box,
+    /// returning its focal length if it was there.
+    fn remove(&mut self, label: &str) -> Option<usize> {
+        match self.find(label) {
+            (slot, Some(position)) => Some(self.boxes[slot].remove(position).1),
+            (_, None) => None,
+        }
+    }
+
+    /// The focal length of the lens with this label.
+    fn get(&self, label: &str) -> Option<usize> {
+        let (slot, position) = self.find(label);
+        position.map(|position| self.boxes[slot][position].1)
+    }
+
+    /// The lenses in a single box, front to back.
+    fn lenses(&self, slot: usize) -> &[Lens<'a>] {
+        &self.boxes[slot]
+    }
+
+    /// Every lens along with its box and its place in the box,
+    /// in the order the light reaches them.
+    fn iter(&self) -> impl Iterator<Item = (usize, usize, &Lens<'a>)> {
+        self.boxes.iter().enumerate().flat_map(|(slot, lenses)| {
+            lenses
+                .iter()
+                .enumerate()
+                .map(move |(position, lens)| (slot, position, lens))
+        })
+    }
+
+    /// Carry out a single step of the HASHMAP procedure.
+    fn apply(&mut self, step: &Instruction<'a>) {
+        match step.operation {
+            Operation::Insert(focus) => self.insert(step.label, focus),
+            Operation::Remove => {
+                self.remove(step.label);
+            }
+        }
+    }
+
+    /// The focusing power of all of the lenses in a single box.
+    fn box_power(&self, slot: usize) -> usize {
+        self.lenses(slot)
+            .iter()
+            .enumerate()
+            .map(|(idx, lens)| (slot + 1) * (idx + 1) * lens.1)
+            .sum()
+    }
+
+    /// The focusing power of the whole lens configuration.
+    fn focusing_power(&self) -> usize {
+        (0..BOXES).map(|slot| self.box_power(slot)).sum()
+    }
+}
+
+impl Default for LensBoxes<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Only boxes with lenses in are shown, one per line,
+/// in the same format as the puzzle uses.
+impl std::fmt::Display for LensBoxes<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for slot in 0..BOXES {
+            let lenses = self.lenses(slot);
+            if lenses.is_empty() {
+                continue;
+            }
+
+            write!(f, "Box {slot}:")?;
+            for Lens(label, focus) in lenses {
+                write!(f, " [{label} {focus}]")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse every step of the initialization sequence.
+fn parse_steps<'a>(data: &[&'a str]) -> ParseResult<Vec<Instruction<'a>>> {
+    data.iter().map(|&step| step.try_into()).collect()
+}
+
 /// Part 2
 /// ------
 ///
@@ -99,66 +219,72 @@ struct Lens<'a>(&'a str, usize);
 /// HASHMAP algorithm using the codes
 /// we were provided?
 fn part_two(data: &[&str]) -> ParseResult<usize> {
-    let steps = data
-        .iter()
-        .map(|&step| step.try_into())
-        .collect::<ParseResult<Vec<Instruction>>>()?;
-
-    let map = mapvent(steps);
-    Ok(map
-        .into_iter()
-        .map(|(slot, lenses)| -> usize {
-            lenses
-                .iter()
-                .enumerate()
-                .map(|(idx, lens)| (slot + 1) * (idx + 1) * lens.1)
-                .sum()
-        })
-        .sum())
+    let mut boxes = LensBoxes::new();
+    for step in parse_steps(data)? {
+        boxes.apply(&step);
+    }
+
+    Ok(boxes.focusing_power())
 }
 
-fn mapvent<'a>(steps: Vec<Instruction<'a>>) -> HashMap<usize, Vec<Lens<'a>>> {
-    let mut map = HashMap::new();
-    for step in steps {
-        let slot = hashvent(step.label) as usize;
-        match step.operation {
-            Operation::Insert(focus) => {
-                map.entry(slot)
-                    .and_modify(|lenses: &mut Vec<Lens<'a>>| {
-                        let position = lenses.iter().position(|lens| lens.0 == step.label);
-                        match position {
-                            Some(position) => {
-                                // This label already exists.
-                                // Replace the focus.
-                                lenses[position].1 = focus;
-                            }
-                            None => {
-                                // Add this label and focus.
-                                lenses.push(Lens(step.label, focus));
-                            }
-                        }
-                    })
-                    .or_insert(vec![Lens(step.label, focus)]);
+/// Look inside the boxes from the command line.
+///
+/// - `steps`: print the boxes after every step, the
+///   same way the puzzle does.
+/// - `lenses`: list every lens once all of the steps are
+///   done, with where it ended up and its focusing power.
+/// - `get <label>...`: the focal length of the lens with
+///   each label once all of the steps are done.
+fn run_command(data: &[&str], command: &str, args: &[String]) -> ParseResult<()> {
+    let mut boxes = LensBoxes::new();
+    for (&text, step) in data.iter().zip(parse_steps(data)?) {
+        boxes.apply(&step);
+        if command == "steps" {
+            println!("After \"{text}\":\n{boxes}");
+        }
+    }
+
+    match command {
+        "steps" => {}
+        "lenses" => {
+            for (slot, position, Lens(label, focus)) in boxes.iter() {
+                let power = (slot + 1) * (position + 1) * focus;
+                println!(
+                    "{label}: box {slot}, slot {}, focal length {focus}, power {power}",
+                    position + 1
+                );
             }
-            Operation::Remove => {
-                map.entry(slot).and_modify(|lenses| {
-                    let position = lenses.iter().position(|lens| lens.0 == step.label);
-                    if let Some(position) = position {
-                        lenses.remove(position);
-                    }
-                });
+        }
+        "get" => {
+            for label in args {
+                match boxes.get(label) {
+                    Some(focus) => println!("{label}: focal length {focus}"),
+                    None => println!("{label}: not in any box"),
+                }
             }
         }
+        _ => panic!("Unknown command {command}"),
     }
-    map
+
+    println!("The total focusing power is {}", boxes.focusing_power());
+    Ok(())
 }
 
 fn main() {
     let input = read_to_string("src/input/day15.txt").expect("Could not read input");
     let data = parse_input(&input);
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(command) = args.first() {
+        run_command(&data, command, &args[1..]).expect("Mapping should succeed");
+        return;
+    }
+
     println!("The total sum of the hashes is {}", part_one(&data));
-    println!("The total focusing power is {}", part_two(&data).expect("Mapping should succeed"));
+    println!(
+        "The total focusing power is {}",
+        part_two(&data).expect("Mapping should succeed")
+    );
 }
 
 #[cfg(test)]
@@ -214,22 +340,62 @@ mod test {
     }
 
     #[test]
-    fn test_mapvent() {
+    fn test_lens_boxes() {
         let input = read_to_string("src/input/day15-test.txt").expect("Could not read example");
         let data = parse_input(&input);
 
-        let steps = data
-            .iter()
-            .map(|&step| step.try_into())
-            .collect::<Result<Vec<Instruction>, _>>()
-            .expect("Steps should convert");
-        let map = mapvent(steps);
+        let mut boxes = LensBoxes::new();
+        for step in parse_steps(&data).expect("Steps should convert") {
+            boxes.apply(&step);
+        }
+
+        assert_eq!(boxes.lenses(0), [Lens("rn", 1), Lens("cm", 2)]);
+        assert_eq!(
+            boxes.lenses(3),
+            [Lens("ot", 7), Lens("ab", 5), Lens("pc", 6)]
+        );
+        assert_eq!(
+            boxes.to_string(),
+            "Box 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n"
+        );
 
-        assert_eq!(map.get(&0), Some(&vec![Lens("rn", 1), Lens("cm", 2)]));
+        let order: Vec<_> = boxes
+            .iter()
+            .map(|(slot, position, lens)| (slot, position, lens.0))
+            .collect();
         assert_eq!(
-            map.get(&3),
-            Some(&vec![Lens("ot", 7), Lens("ab", 5), Lens("pc", 6)])
+            order,
+            [
+                (0, 0, "rn"),
+                (0, 1, "cm"),
+                (3, 0, "ot"),
+                (3, 1, "ab"),
+                (3, 2, "pc")
+            ]
         );
+
+        assert_eq!(boxes.box_power(0), 1 + 4);
+        assert_eq!(boxes.box_power(3), 28 + 40 + 72);
+        assert_eq!(boxes.focusing_power(), 145);
+    }
+
+    #[test]
+    fn test_insert_remove() {
+        let mut boxes = LensBoxes::new();
+
+        boxes.insert("rn", 1);
+        boxes.insert("cm", 2);
+        assert_eq!(boxes.get("rn"), Some(1));
+        assert_eq!(boxes.get("qp"), None);
+
+        // Replacing a lens keeps its place.
+        boxes.insert("rn", 5);
+        assert_eq!(boxes.lenses(0), [Lens("rn", 5), Lens("cm", 2)]);
+
+        assert_eq!(boxes.remove("rn"), Some(5));
+        assert_eq!(boxes.remove("rn"), None);
+        assert_eq!(boxes.lenses(0), [Lens("cm", 2)]);
+        assert_eq!(boxes.to_string(), "Box 0: [cm 2]\n");
     }
 
     #[test]
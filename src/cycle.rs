@@ -0,0 +1,175 @@
+//! Cycle detection for simulations
+//!
+//! Plenty of puzzles ask for the state of a simulation after
+//! far too many steps to run. Almost all of them settle into
+//! a loop, so we find it and jump straight to the answer.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// The states of a simulation that eventually repeats itself.
+///
+/// The simulation runs for `start` steps (mu) before entering
+/// a loop of `length` steps (lambda). Every state up to the end
+/// of the first time around the loop is kept, so the state at
+/// any step can be looked up without running anything again.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cycle<T> {
+    /// The first step that is part of the loop.
+    pub start: u64,
+    /// The number of steps it takes to go around the loop once.
+    pub length: u64,
+    states: Vec<T>,
+}
+
+impl<T> Cycle<T> {
+    /// The start and length of the loop, as `(mu, lambda)`.
+    pub fn mu_lambda(&self) -> (u64, u64) {
+        (self.start, self.length)
+    }
+
+    /// The state after `step` steps of the simulation.
+    pub fn state_at(&self, step: u64) -> &T {
+        let step = if step < self.start {
+            step
+        } else {
+            self.start + (step - self.start) % self.length
+        };
+        &self.states[step as usize]
+    }
+
+    /// Every state before the loop, and once around it.
+    pub fn states(&self) -> &[T] {
+        &self.states
+    }
+}
+
+/// Find the cycle in the simulation `f` starting at `initial`
+/// with Brent's algorithm.
+///
+/// This only needs to compare states, so it also works for
+/// states that can't be hashed. The states are only recorded once the
+/// length of the loop is known, and only as far as they're needed.
+pub fn brent<T, F>(mut f: F, initial: T) -> Cycle<T>
+where
+    F: FnMut(T) -> T,
+    T: PartialEq + Clone,
+{
+    // Find the length of the loop, by teleporting the tortoise
+    // to the hare every time the hare has run a power of two.
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = f(initial.clone());
+
+    while tortoise != hare {
+        if length == power {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = f(hare);
+        length += 1;
+    }
+
+    // Then walk two pointers a loop apart from the start until
+    // they meet. The one in front records every state we need,
+    // and the one behind just reads them back.
+    let mut states = vec![initial];
+    let mut step = |states: &mut Vec<T>| {
+        let last = states
+            .last()
+            .expect("States always start with the initial state");
+        let next = f(last.clone());
+        states.push(next);
+    };
+
+    for _ in 0..length {
+        step(&mut states);
+    }
+
+    let mut start = 0;
+    while states[start] != states[start + length] {
+        step(&mut states);
+        start += 1;
+    }
+
+    // The last state is the start of the loop again.
+    states.pop();
+
+    Cycle {
+        start: start as u64,
+        length: length as u64,
+        states,
+    }
+}
+
+/// Find the cycle in the simulation `f` starting at `initial`
+/// by remembering every state we've seen.
+///
+/// This runs the simulation exactly once around the loop,
+/// at the cost of hashing every state.
+pub fn hashed<T, F>(mut f: F, initial: T) -> Cycle<T>
+where
+    F: FnMut(T) -> T,
+    T: Eq + Hash + Clone,
+{
+    let mut seen = HashMap::new();
+    let mut states = Vec::new();
+    let mut state = initial;
+
+    loop {
+        if let Some(&start) = seen.get(&state) {
+            let length = states.len() as u64 - start;
+            return Cycle {
+                start,
+                length,
+                states,
+            };
+        }
+
+        seen.insert(state.clone(), states.len() as u64);
+        states.push(state.clone());
+        state = f(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_func(val: u32) -> u32 {
+        (val * val + 1) % 255
+    }
+
+    #[test]
+    fn test_brent() {
+        let cycle = brent(test_func, 3);
+
+        assert_eq!(cycle.mu_lambda(), (2, 6));
+        assert_eq!(cycle.states().len(), 8);
+    }
+
+    #[test]
+    fn test_hashed() {
+        assert_eq!(hashed(test_func, 3), brent(test_func, 3));
+
+        // A simulation that's already in its loop.
+        let cycle = hashed(|val| (val + 1) % 5, 0);
+        assert_eq!(cycle.mu_lambda(), (0, 5));
+    }
+
+    #[test]
+    fn test_state_at() {
+        let cycle = brent(test_func, 3);
+
+        let mut val = 3;
+        for step in 0..100 {
+            assert_eq!(*cycle.state_at(step), val, "step {step}");
+            val = test_func(val);
+        }
+
+        // Far off steps are just looked up.
+        assert_eq!(*cycle.state_at(1_000_000_000_000), *cycle.state_at(4));
+    }
+}
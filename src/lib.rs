@@ -4,6 +4,8 @@ use std::num::ParseIntError;
 
 use thiserror::Error;
 
+pub mod cycle;
+
 #[derive(Clone, PartialEq, Debug, Error)]
 /// The common error type for parsing.
 pub enum ParseError {